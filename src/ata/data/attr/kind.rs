@@ -0,0 +1,250 @@
+/*!
+Semantic classification of SMART attributes.
+
+Vendors put the same thing under different attribute IDs and names, and, conversely, reuse the same ID for different things. This module maps rendered attributes to vendor-neutral [`Kind`](enum.Kind.html)s, along with values converted into fixed units, so that monitoring rules can be written once per kind rather than once per vendor.
+
+## Example
+
+```
+use hdd::ata::data::attr::kind::{classify, Kind};
+
+...
+
+for attr in &attrs {
+	if let Some(c) = classify(attr, &id) {
+		if c.kind == Kind::PendingSectors && c.value.unwrap_or(0.) > 0. {
+			println!("drive has pending sectors");
+		}
+	}
+}
+```
+*/
+
+use super::{SmartAttribute, valid_value};
+use super::raw::Unit;
+use ata::data::id::{Id, RPM};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serializable", derive(Serialize))]
+pub enum Kind {
+	/// Number of reallocated sectors (or blocks, for SSDs)
+	ReallocatedSectors,
+	/// Number of attempts to reallocate sectors, successful or not
+	ReallocationEvents,
+	/// Number of sectors waiting to be reallocated
+	PendingSectors,
+	/// Number of uncorrectable errors found during off-line scan
+	OfflineUncorrectable,
+	/// Number of uncorrectable errors reported to the host
+	ReportedUncorrectable,
+	/// Number of interface CRC errors
+	CRCErrors,
+	/// Drive temperature, in °C
+	Temperature,
+	/// Airflow temperature, in °C
+	AirflowTemperature,
+	/// Power-on time, in seconds
+	PowerOnTime,
+	PowerCycles,
+	StartStopCycles,
+	LoadCycles,
+	SpinRetries,
+	CommandTimeouts,
+	/// Remaining life of flash memory, in percent (100 for a new drive)
+	WearLevel,
+	/// Bytes written by the host
+	BytesWritten,
+	/// Bytes read by the host
+	BytesRead,
+}
+
+impl Kind {
+	/// Returns the name of the kind, suitable for use in labels and keys.
+	pub fn name(&self) -> &'static str {
+		use self::Kind::*;
+		match *self {
+			ReallocatedSectors => "reallocated-sectors",
+			ReallocationEvents => "reallocation-events",
+			PendingSectors => "pending-sectors",
+			OfflineUncorrectable => "offline-uncorrectable",
+			ReportedUncorrectable => "reported-uncorrectable",
+			CRCErrors => "crc-errors",
+			Temperature => "temperature",
+			AirflowTemperature => "airflow-temperature",
+			PowerOnTime => "power-on-time",
+			PowerCycles => "power-cycles",
+			StartStopCycles => "start-stop-cycles",
+			LoadCycles => "load-cycles",
+			SpinRetries => "spin-retries",
+			CommandTimeouts => "command-timeouts",
+			WearLevel => "wear-level",
+			BytesWritten => "bytes-written",
+			BytesRead => "bytes-read",
+		}
+	}
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serializable", derive(Serialize))]
+pub struct Classification {
+	/// ID of the attribute this classification was made for
+	pub id: u8,
+	pub kind: Kind,
+	/// Value converted into the unit of the `kind` (see [`Kind`](enum.Kind.html)); `None` if it cannot be determined
+	pub value: Option<f64>,
+}
+
+const MIB: f64 = 1024. * 1024.;
+const GIB: f64 = 1024. * MIB;
+
+// How to turn the raw value into the value of the kind
+#[derive(Debug, Clone, Copy)]
+enum Conversion {
+	/// Primary raw value as is
+	Count,
+	/// Primary raw value, in °C, or its lowest byte if the attribute was not rendered as a temperature
+	Celsius,
+	/// Primary raw value, in seconds, or in units of given number of seconds if the attribute was not rendered as time
+	Seconds(f64),
+	/// Normalized (not raw) value
+	Normalized,
+	/// Primary raw value multiplied by given number of bytes
+	Bytes(f64),
+	/// Primary raw value, in logical sectors
+	Sectors,
+}
+
+use self::Kind::*;
+use self::Conversion::*;
+
+// names of attributes as they appear in drivedb
+static NAMES: &[(&str, Kind, Conversion)] = &[
+	("Reallocated_Sector_Ct", ReallocatedSectors, Count),
+	("Reallocated_Block_Count", ReallocatedSectors, Count),
+	("Reallocate_NAND_Blk_Cnt", ReallocatedSectors, Count),
+	("Retired_Block_Count", ReallocatedSectors, Count),
+
+	("Reallocated_Event_Count", ReallocationEvents, Count),
+
+	("Current_Pending_Sector", PendingSectors, Count),
+	("Current_Pending_ECC_Cnt", PendingSectors, Count),
+
+	("Offline_Uncorrectable", OfflineUncorrectable, Count),
+
+	("Reported_Uncorrect", ReportedUncorrectable, Count),
+
+	("UDMA_CRC_Error_Count", CRCErrors, Count),
+	("CRC_Error_Count", CRCErrors, Count),
+	("SATA_CRC_Error", CRCErrors, Count),
+
+	("Temperature_Celsius", Temperature, Celsius),
+	("Temperature_Internal", Temperature, Celsius),
+	("Drive_Temperature", Temperature, Celsius),
+	("Airflow_Temperature_Cel", AirflowTemperature, Celsius),
+
+	("Power_On_Hours", PowerOnTime, Seconds(3600.)),
+	("Power_On_Hours_and_Msec", PowerOnTime, Seconds(3600.)),
+	("Power_On_Minutes", PowerOnTime, Seconds(60.)),
+	("Power_On_Half_Minutes", PowerOnTime, Seconds(30.)),
+	("Power_On_Seconds", PowerOnTime, Seconds(1.)),
+
+	("Power_Cycle_Count", PowerCycles, Count),
+	("Start_Stop_Count", StartStopCycles, Count),
+	("Load_Cycle_Count", LoadCycles, Count),
+	("Spin_Retry_Count", SpinRetries, Count),
+	("Command_Timeout", CommandTimeouts, Count),
+
+	("Wear_Leveling_Count", WearLevel, Normalized),
+	("Media_Wearout_Indicator", WearLevel, Normalized),
+	("Percent_Lifetime_Remain", WearLevel, Normalized),
+	("Perc_Rated_Life_Remain", WearLevel, Normalized),
+	("Remaining_Lifetime_Perc", WearLevel, Normalized),
+	("SSD_Life_Left", WearLevel, Normalized),
+
+	("Total_LBAs_Written", BytesWritten, Sectors),
+	("Host_Writes_MiB", BytesWritten, Bytes(MIB)),
+	("Host_Writes_32MiB", BytesWritten, Bytes(32. * MIB)),
+	("Host_Writes_GiB", BytesWritten, Bytes(GIB)),
+	("Lifetime_Writes_GiB", BytesWritten, Bytes(GIB)),
+
+	("Total_LBAs_Read", BytesRead, Sectors),
+	("Host_Reads_MiB", BytesRead, Bytes(MIB)),
+	("Host_Reads_32MiB", BytesRead, Bytes(32. * MIB)),
+	("Host_Reads_GiB", BytesRead, Bytes(GIB)),
+	("Lifetime_Reads_GiB", BytesRead, Bytes(GIB)),
+];
+
+// attributes that are not named (i.e. there was no drivedb to render them with) are classified using their IDs, as long as the meaning of the ID is more or less universal
+fn by_id(id: u8, ssd: bool) -> Option<(Kind, Conversion)> {
+	Some(match id {
+		4 => (StartStopCycles, Count),
+		5 => (ReallocatedSectors, Count),
+		9 => (PowerOnTime, Seconds(3600.)),
+		10 => (SpinRetries, Count),
+		12 => (PowerCycles, Count),
+		187 => (ReportedUncorrectable, Count),
+		188 => (CommandTimeouts, Count),
+		190 => (AirflowTemperature, Celsius),
+		193 => (LoadCycles, Count),
+		194 => (Temperature, Celsius),
+		196 => (ReallocationEvents, Count),
+		197 => (PendingSectors, Count),
+		198 => (OfflineUncorrectable, Count),
+		199 => (CRCErrors, Count),
+		241 => (BytesWritten, Sectors),
+		242 => (BytesRead, Sectors),
+		// these are only used for wear level by SSD vendors; hard drives use them for all sorts of things
+		177 | 231 | 233 if ssd => (WearLevel, Normalized),
+		_ => return None,
+	})
+}
+
+fn convert(attr: &SmartAttribute, conversion: Conversion, sector_size: u32) -> Option<f64> {
+	let raw = attr.raw.primary();
+	match conversion {
+		Count => raw,
+		Celsius => match attr.raw.unit() {
+			Unit::Celsius => raw,
+			_ => raw.map(|x| (x as u64 & 0xff) as i8 as f64),
+		},
+		Seconds(unit) => match attr.raw.unit() {
+			Unit::Seconds => raw,
+			_ => raw.map(|x| x * unit),
+		},
+		Normalized => valid_value(attr.value).map(|v| v as f64),
+		Bytes(unit) => raw.map(|x| x * unit),
+		Sectors => raw.map(|x| x * sector_size as f64),
+	}
+}
+
+/**
+Classifies rendered attribute using its drivedb name (if any), ID, and drive type (as reported in `id`); returns `None` if the attribute is not something this module knows about.
+
+Attributes that have a drivedb name are only classified by that name, since vendors tend to rename attributes when they reuse IDs for something else.
+*/
+pub fn classify(attr: &SmartAttribute, id: &Id) -> Option<Classification> {
+	let ssd = match id.rpm {
+		RPM::NonRotating => true,
+		RPM::Unknown | RPM::RPM(_) => false,
+	};
+
+	let (kind, conversion) = match attr.name {
+		Some(ref name) => NAMES.iter()
+			.find(|&&(n, _, _)| n == name)
+			.map(|&(_, kind, conversion)| (kind, conversion))?,
+		None => by_id(attr.id, ssd)?,
+	};
+
+	Some(Classification {
+		id: attr.id,
+		kind,
+		value: convert(attr, conversion, id.sector_size_log),
+	})
+}
+
+/// Classifies every attribute in `attrs` (see [`classify`](fn.classify.html)), skipping those that cannot be classified.
+pub fn classify_all(attrs: &[SmartAttribute], id: &Id) -> Vec<Classification> {
+	attrs.iter()
+		.filter_map(|attr| classify(attr, id))
+		.collect()
+}
@@ -1,8 +1,61 @@
 pub mod raw;
+pub mod kind;
 
 use std::collections::HashMap;
 use drivedb;
 
+/// Attribute status, evaluated from its normalized values and threshold
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serializable", derive(Serialize))]
+pub enum Status {
+	Ok,
+	/// Current value is at or below the threshold
+	FailingNow,
+	/// Current value is fine, but the worst one is at or below the threshold
+	FailedInPast,
+	/// Threshold or values are not available, or are invalid
+	Unknown,
+}
+
+impl Status {
+	pub fn name(&self) -> &'static str {
+		use self::Status::*;
+		match *self {
+			Ok => "ok",
+			FailingNow => "failing-now",
+			FailedInPast => "failed-in-past",
+			Unknown => "unknown",
+		}
+	}
+}
+
+// normalized values of 0x00, 0xfe and 0xff are invalid
+pub(crate) fn valid_value(value: Option<u8>) -> Option<u8> {
+	match value {
+		Some(0x00) | Some(0xfe) | Some(0xff) => None,
+		v => v,
+	}
+}
+
+fn evaluate_status(value: Option<u8>, worst: Option<u8>, thresh: Option<u8>) -> Status {
+	use self::Status::*;
+	let thresh = match thresh {
+		None => return Unknown,
+		Some(0x00) => return Ok, // always passing
+		Some(0xfe) => return Unknown, // invalid
+		Some(0xff) => return FailingNow, // always failing
+		Some(t) => t,
+	};
+
+	match (valid_value(value), valid_value(worst)) {
+		(Some(v), _) if v <= thresh => FailingNow,
+		(_, Some(w)) if w <= thresh => FailedInPast,
+		(Some(_), _) => Ok,
+		// current value is either part of the raw value or invalid, and the worst one (if any) is above the threshold
+		(None, _) => Unknown,
+	}
+}
+
 #[derive(Debug)]
 #[cfg_attr(feature = "serializable", derive(Serialize))]
 pub struct SmartAttribute {
@@ -21,18 +74,20 @@ pub struct SmartAttribute {
 	pub flags: u16,
 
 	// contains None if `raw` is rendered using byte that usually covers this value
-	// TODO? 0x00 | 0xfe | 0xff are invalid
+	// 0x00 | 0xfe | 0xff are invalid (see `status`)
 	pub value: Option<u8>,
 	// contains None if `raw` is rendered using byte that usually covers this value
 	pub worst: Option<u8>,
 
 	pub raw: raw::Raw,
 
-	pub thresh: Option<u8>, // requested separately; TODO? 0x00 is "always passing", 0xff is "always failing", 0xfe is invalid
+	pub thresh: Option<u8>, // requested separately; 0x00 is "always passing", 0xff is "always failing", 0xfe is invalid
+
+	pub status: Status, // evaluated from `value`, `worst` and `thresh`
 }
 
 pub fn parse_smart_values(data: &Vec<u8>, raw_thresh: &Vec<u8>, meta: &Option<drivedb::DriveMeta>) -> Vec<SmartAttribute> {
-	// TODO cover bytes 0..1 362..511 of data
+	// bytes 0..1 and 362..511 of data are covered in `ata::data::smart_data`
 	// XXX what if some drive reports the same attribute multiple times?
 	// TODO return None if data.len() < 512
 
@@ -56,6 +111,15 @@ pub fn parse_smart_values(data: &Vec<u8>, raw_thresh: &Vec<u8>, meta: &Option<dr
 		let attr = meta.as_ref().map(|meta| meta.render_attribute(id)).unwrap_or(None);
 		let is_in_raw = |c| attr.as_ref().map(|a| a.byte_order.contains(c)).unwrap_or(false);
 
+		let value = if !is_in_raw('v') {
+			Some(data[offset + 3])
+		} else { None };
+		let worst = if !is_in_raw('w') {
+			Some(data[offset + 4])
+		} else { None };
+		// .get() returns Option<&T>, but threshs would not live long enough, and it's just easier to copy u8 using this map
+		let thresh = threshs.get(&data[offset]).map(|&t| t);
+
 		attrs.push(SmartAttribute {
 			id: id,
 
@@ -72,17 +136,14 @@ pub fn parse_smart_values(data: &Vec<u8>, raw_thresh: &Vec<u8>, meta: &Option<dr
 			self_preserving: flags & (1<<5) != 0,
 			flags:           flags & (!0b11_1111),
 
-			value: if !is_in_raw('v') {
-				Some(data[offset + 3])
-			} else { None },
-			worst: if !is_in_raw('w') {
-				Some(data[offset + 4])
-			} else { None },
+			value,
+			worst,
 
 			raw: raw::Raw::from_raw_entry(&data[offset .. offset + 12], &attr),
 
-			// .get() returns Option<&T>, but threshs would not live long enough, and it's just easier to copy u8 using this map
-			thresh: threshs.get(&data[offset]).map(|&t| t),
+			thresh,
+
+			status: evaluate_status(value, worst, thresh),
 		})
 	}
 	attrs
@@ -29,6 +29,96 @@ pub enum Raw {
 	CelsiusMinMax { current: u8, min: u8, max: u8 },
 }
 
+/// Unit of the numeric value of the raw attribute
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serializable", derive(Serialize))]
+pub enum Unit {
+	Count,
+	Seconds,
+	Celsius,
+	/// Result of division of one component by another (see `Raw24div`)
+	Ratio,
+}
+
+impl Unit {
+	pub fn name(&self) -> &'static str {
+		use self::Unit::*;
+		match *self {
+			Count => "count",
+			Seconds => "seconds",
+			Celsius => "celsius",
+			Ratio => "ratio",
+		}
+	}
+}
+
+fn indexed<T: Into<f64> + Copy>(vals: &[T], first: usize) -> Vec<(String, Unit, f64)> {
+	vals.iter().enumerate()
+		.map(|(i, &x)| ((first + i).to_string(), Unit::Count, x.into()))
+		.collect()
+}
+
+impl Raw {
+	/// Returns unit of the [`primary`](#method.primary) value.
+	pub fn unit(&self) -> Unit {
+		use self::Raw::*;
+		match *self {
+			Raw8(_) | Raw16(_) | Raw64(_) | Raw16opt16(..) | Raw16avg16 { .. } | Raw24opt8(..) => Unit::Count,
+			Raw24div(..) => Unit::Ratio,
+			Minutes(_) | Seconds(_) | HoursMilliseconds(..) => Unit::Seconds,
+			Celsius(_) | CelsiusMinMax { .. } => Unit::Celsius,
+		}
+	}
+
+	/**
+	Returns the main numeric value of the attribute, converted into its [`unit`](#method.unit) (e.g. minutes are converted into seconds).
+
+	Returns `None` for `Raw8` and `Raw16`, which are nothing but a list of values (see [`secondary`](#method.secondary)), and for `Raw24div` with zero denominator.
+	*/
+	pub fn primary(&self) -> Option<f64> {
+		use self::Raw::*;
+		match *self {
+			Raw8(_) | Raw16(_) => None,
+			Raw64(x) => Some(x as f64),
+			Raw16opt16(x, _) => Some(x as f64),
+			Raw16avg16 { value, .. } => Some(value as f64),
+			Raw24opt8(x, _) => Some(x as f64),
+			Raw24div(_, 0) => None,
+			Raw24div(x, y) => Some(x as f64 / y as f64),
+			Minutes(x) => Some(x as f64 * 60.),
+			Seconds(x) => Some(x as f64),
+			HoursMilliseconds(h, ms) => Some(h as f64 * 3600. + ms as f64 / 1000.),
+			Celsius(x) => Some(x as f64),
+			CelsiusMinMax { current, .. } => Some(current as f64),
+		}
+	}
+
+	/**
+	Returns named components of the raw value, other than the primary one, along with their units.
+
+	These are: every element of `Raw8` and `Raw16` (named by their index, starting with 0); optional elements of `Raw16opt16` and `Raw24opt8` (named by their index, starting with 1, for 0 is the primary value); `average` of `Raw16avg16`; `numerator` and `denominator` of `Raw24div` (which are counts, unlike the primary value); `min` and `max` of `CelsiusMinMax`.
+	*/
+	pub fn secondary(&self) -> Vec<(String, Unit, f64)> {
+		use self::Raw::*;
+		match *self {
+			Raw8(ref vals) => indexed(vals, 0),
+			Raw16(ref vals) => indexed(vals, 0),
+			Raw16opt16(_, Some(ref vals)) => indexed(vals, 1),
+			Raw24opt8(_, Some(ref vals)) => indexed(vals, 1),
+			Raw16avg16 { average, .. } => vec![("average".to_string(), Unit::Count, average as f64)],
+			Raw24div(x, y) => vec![
+				("numerator".to_string(), Unit::Count, x as f64),
+				("denominator".to_string(), Unit::Count, y as f64),
+			],
+			CelsiusMinMax { min, max, .. } => vec![
+				("min".to_string(), Unit::Celsius, min as f64),
+				("max".to_string(), Unit::Celsius, max as f64),
+			],
+			Raw64(_) | Raw16opt16(_, None) | Raw24opt8(_, None) | Minutes(_) | Seconds(_) | HoursMilliseconds(..) | Celsius(_) => vec![],
+		}
+	}
+}
+
 fn write_vec<T>(f: &mut fmt::Formatter, vec: &Vec<T>) -> fmt::Result
 where T: fmt::Display {
 	let mut values = vec.iter();
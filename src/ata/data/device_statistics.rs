@@ -0,0 +1,313 @@
+/*!
+Device Statistics log (log address 04h), as seen in ACS-4, 9.5.
+
+Unlike SMART attributes, the meaning of these statistics is standardized, so there is no need to look them up in drivedb.
+*/
+
+use byteorder::{ByteOrder, LittleEndian};
+
+/// Standard pages of the Device Statistics log
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serializable", derive(Serialize))]
+pub enum Page {
+	General = 0x01,
+	FreeFall = 0x02,
+	RotatingMedia = 0x03,
+	GeneralErrors = 0x04,
+	Temperature = 0x05,
+	Transport = 0x06,
+	SolidState = 0x07,
+}
+
+impl Page {
+	fn from(page: u8) -> Option<Self> {
+		use self::Page::*;
+		match page {
+			0x01 => Some(General),
+			0x02 => Some(FreeFall),
+			0x03 => Some(RotatingMedia),
+			0x04 => Some(GeneralErrors),
+			0x05 => Some(Temperature),
+			0x06 => Some(Transport),
+			0x07 => Some(SolidState),
+			_ => None,
+		}
+	}
+
+	pub fn name(&self) -> &'static str {
+		use self::Page::*;
+		match *self {
+			General => "general",
+			FreeFall => "free-fall",
+			RotatingMedia => "rotating-media",
+			GeneralErrors => "general-errors",
+			Temperature => "temperature",
+			Transport => "transport",
+			SolidState => "solid-state",
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serializable", derive(Serialize))]
+pub enum Statistic {
+	// General Statistics
+	LifetimePowerOnResets,
+	PowerOnHours,
+	LogicalSectorsWritten,
+	WriteCommands,
+	LogicalSectorsRead,
+	ReadCommands,
+	/// Milliseconds since power-on, or since the date and time was set with SET DATE & TIME EXT
+	DateAndTimeTimestamp,
+	PendingErrorCount,
+
+	// Free-Fall Statistics
+	FreeFallEvents,
+	OverlimitShockEvents,
+
+	// Rotating Media Statistics
+	SpindleMotorPowerOnHours,
+	HeadFlyingHours,
+	HeadLoadEvents,
+	ReallocatedLogicalSectors,
+	ReadRecoveryAttempts,
+	MechanicalStartFailures,
+	ReallocationCandidateLogicalSectors,
+	HighPriorityUnloadEvents,
+
+	// General Errors Statistics
+	ReportedUncorrectableErrors,
+	ResetsBetweenCommandAcceptanceAndCompletion,
+	PhysicalElementStatusChanged,
+
+	// Temperature Statistics
+	CurrentTemperature,
+	AverageShortTermTemperature,
+	AverageLongTermTemperature,
+	HighestTemperature,
+	LowestTemperature,
+	HighestAverageShortTermTemperature,
+	LowestAverageShortTermTemperature,
+	HighestAverageLongTermTemperature,
+	LowestAverageLongTermTemperature,
+	/// Minutes spent above the specified maximum operating temperature
+	TimeInOverTemperature,
+	SpecifiedMaximumOperatingTemperature,
+	/// Minutes spent below the specified minimum operating temperature
+	TimeInUnderTemperature,
+	SpecifiedMinimumOperatingTemperature,
+
+	// Transport Statistics
+	HardwareResets,
+	ASREvents,
+	InterfaceCRCErrors,
+
+	// Solid State Device Statistics
+	/// Vendor-specific estimate of the percentage of life used, might exceed 100
+	PercentageUsedEnduranceIndicator,
+}
+
+/// How the 56-bit value field of the statistic should be interpreted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+	U8,
+	U32,
+	U48,
+	/// Signed 8-bit temperature, in °C; `0x80` means the value is not available
+	Celsius,
+}
+
+// (page, offset, statistic, format)
+static STATISTICS: &[(Page, u16, Statistic, Format)] = {
+	use self::Page::*;
+	use self::Statistic::*;
+	use self::Format::*;
+	&[
+		(General, 0x08, LifetimePowerOnResets, U32),
+		(General, 0x10, PowerOnHours, U32),
+		(General, 0x18, LogicalSectorsWritten, U48),
+		(General, 0x20, WriteCommands, U48),
+		(General, 0x28, LogicalSectorsRead, U48),
+		(General, 0x30, ReadCommands, U48),
+		(General, 0x38, DateAndTimeTimestamp, U48),
+		(General, 0x40, PendingErrorCount, U32),
+
+		(FreeFall, 0x08, FreeFallEvents, U32),
+		(FreeFall, 0x10, OverlimitShockEvents, U32),
+
+		(RotatingMedia, 0x08, SpindleMotorPowerOnHours, U32),
+		(RotatingMedia, 0x10, HeadFlyingHours, U32),
+		(RotatingMedia, 0x18, HeadLoadEvents, U32),
+		(RotatingMedia, 0x20, ReallocatedLogicalSectors, U32),
+		(RotatingMedia, 0x28, ReadRecoveryAttempts, U32),
+		(RotatingMedia, 0x30, MechanicalStartFailures, U32),
+		(RotatingMedia, 0x38, ReallocationCandidateLogicalSectors, U32),
+		(RotatingMedia, 0x40, HighPriorityUnloadEvents, U32),
+
+		(GeneralErrors, 0x08, ReportedUncorrectableErrors, U32),
+		(GeneralErrors, 0x10, ResetsBetweenCommandAcceptanceAndCompletion, U32),
+		(GeneralErrors, 0x18, PhysicalElementStatusChanged, U32),
+
+		(Temperature, 0x08, CurrentTemperature, Celsius),
+		(Temperature, 0x10, AverageShortTermTemperature, Celsius),
+		(Temperature, 0x18, AverageLongTermTemperature, Celsius),
+		(Temperature, 0x20, HighestTemperature, Celsius),
+		(Temperature, 0x28, LowestTemperature, Celsius),
+		(Temperature, 0x30, HighestAverageShortTermTemperature, Celsius),
+		(Temperature, 0x38, LowestAverageShortTermTemperature, Celsius),
+		(Temperature, 0x40, HighestAverageLongTermTemperature, Celsius),
+		(Temperature, 0x48, LowestAverageLongTermTemperature, Celsius),
+		(Temperature, 0x50, TimeInOverTemperature, U32),
+		(Temperature, 0x58, SpecifiedMaximumOperatingTemperature, Celsius),
+		(Temperature, 0x60, TimeInUnderTemperature, U32),
+		(Temperature, 0x68, SpecifiedMinimumOperatingTemperature, Celsius),
+
+		(Transport, 0x08, HardwareResets, U32),
+		(Transport, 0x10, ASREvents, U32),
+		(Transport, 0x18, InterfaceCRCErrors, U32),
+
+		(SolidState, 0x08, PercentageUsedEnduranceIndicator, U8),
+	]
+};
+
+impl Statistic {
+	/// Returns the name of the statistic, suitable for use in labels and keys.
+	pub fn name(&self) -> &'static str {
+		use self::Statistic::*;
+		match *self {
+			LifetimePowerOnResets => "lifetime-power-on-resets",
+			PowerOnHours => "power-on-hours",
+			LogicalSectorsWritten => "logical-sectors-written",
+			WriteCommands => "write-commands",
+			LogicalSectorsRead => "logical-sectors-read",
+			ReadCommands => "read-commands",
+			DateAndTimeTimestamp => "date-and-time-timestamp",
+			PendingErrorCount => "pending-error-count",
+
+			FreeFallEvents => "free-fall-events",
+			OverlimitShockEvents => "overlimit-shock-events",
+
+			SpindleMotorPowerOnHours => "spindle-motor-power-on-hours",
+			HeadFlyingHours => "head-flying-hours",
+			HeadLoadEvents => "head-load-events",
+			ReallocatedLogicalSectors => "reallocated-logical-sectors",
+			ReadRecoveryAttempts => "read-recovery-attempts",
+			MechanicalStartFailures => "mechanical-start-failures",
+			ReallocationCandidateLogicalSectors => "reallocation-candidate-logical-sectors",
+			HighPriorityUnloadEvents => "high-priority-unload-events",
+
+			ReportedUncorrectableErrors => "reported-uncorrectable-errors",
+			ResetsBetweenCommandAcceptanceAndCompletion => "resets-between-command-acceptance-and-completion",
+			PhysicalElementStatusChanged => "physical-element-status-changed",
+
+			CurrentTemperature => "current-temperature",
+			AverageShortTermTemperature => "average-short-term-temperature",
+			AverageLongTermTemperature => "average-long-term-temperature",
+			HighestTemperature => "highest-temperature",
+			LowestTemperature => "lowest-temperature",
+			HighestAverageShortTermTemperature => "highest-average-short-term-temperature",
+			LowestAverageShortTermTemperature => "lowest-average-short-term-temperature",
+			HighestAverageLongTermTemperature => "highest-average-long-term-temperature",
+			LowestAverageLongTermTemperature => "lowest-average-long-term-temperature",
+			TimeInOverTemperature => "time-in-over-temperature",
+			SpecifiedMaximumOperatingTemperature => "specified-maximum-operating-temperature",
+			TimeInUnderTemperature => "time-in-under-temperature",
+			SpecifiedMinimumOperatingTemperature => "specified-minimum-operating-temperature",
+
+			HardwareResets => "hardware-resets",
+			ASREvents => "asr-events",
+			InterfaceCRCErrors => "interface-crc-errors",
+
+			PercentageUsedEnduranceIndicator => "percentage-used-endurance-indicator",
+		}
+	}
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serializable", derive(Serialize))]
+pub struct DeviceStatistic {
+	pub page: Page,
+	pub statistic: Statistic,
+	/// `None` if the device reports the value as invalid (e.g. the temperature was never measured)
+	pub value: Option<i64>,
+	/// Whether the value is normalized (i.e. it is not an exact count or measurement)
+	pub normalized: bool,
+	/// Whether the condition monitored with Device Statistics Notification (DSN) feature is met
+	pub monitored_condition_met: bool,
+}
+
+// flags in the upper byte of every statistic
+const SUPPORTED: u64 = 1<<63;
+const VALID: u64 = 1<<62;
+const NORMALIZED: u64 = 1<<61;
+const MONITORED_CONDITION_MET: u64 = 1<<59;
+
+fn parse_value(qword: u64, format: Format) -> Option<i64> {
+	if qword & VALID == 0 {
+		return None;
+	}
+
+	match format {
+		Format::U8 => Some((qword & 0xff) as i64),
+		Format::U32 => Some((qword & 0xffff_ffff) as i64),
+		Format::U48 => Some((qword & 0xffff_ffff_ffff) as i64),
+		Format::Celsius => match qword as u8 {
+			0x80 => None,
+			t => Some(t as i8 as i64),
+		},
+	}
+}
+
+/**
+Parses Device Statistics log read in its entirety, i.e. starting with the list of supported pages (page 00h); returns `None` if `data` is too short.
+
+Statistics that are not supported by the device are skipped, as well as pages that are not standard (vendor-specific ones, for example).
+*/
+pub fn parse_device_statistics(data: &[u8]) -> Option<Vec<DeviceStatistic>> {
+	if data.len() < 512 {
+		return None;
+	}
+
+	// list of supported pages: number of entries at byte 8, entries themselves follow
+	let count = data[8] as usize;
+	let supported = &data[9 .. 9 + count.min(512 - 9)];
+
+	let mut stats = vec![];
+	for &page_number in supported {
+		let page = match Page::from(page_number) {
+			Some(page) => page,
+			None => continue,
+		};
+
+		let offset = page_number as usize * 512;
+		let page_data = match data.get(offset .. offset + 512) {
+			Some(page_data) => page_data,
+			None => continue, // the caller did not read that far
+		};
+
+		// header: revision number in bytes 0-1, page number in byte 2
+		if page_data[2] != page_number {
+			warn!("device statistics page {:#04x} has unexpected page number {:#04x} in its header, skipping", page_number, page_data[2]);
+			continue;
+		}
+
+		for &(_, stat_offset, statistic, format) in STATISTICS.iter().filter(|s| s.0 == page) {
+			let stat_offset = stat_offset as usize;
+			let qword = LittleEndian::read_u64(&page_data[stat_offset .. stat_offset + 8]);
+			if qword & SUPPORTED == 0 {
+				continue;
+			}
+
+			stats.push(DeviceStatistic {
+				page,
+				statistic,
+				value: parse_value(qword, format),
+				normalized: qword & NORMALIZED != 0,
+				monitored_condition_met: qword & MONITORED_CONDITION_MET != 0,
+			});
+		}
+	}
+
+	Some(stats)
+}
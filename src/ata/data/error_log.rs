@@ -0,0 +1,210 @@
+/*!
+SMART error logs: summary (log address 01h), comprehensive (02h), and extended comprehensive (GPL log address 03h), as seen in ATA8-ACS, A.7, A.4 and A.5.
+*/
+
+use byteorder::{ByteOrder, LittleEndian};
+
+/// State the device was in when the error occurred
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serializable", derive(Serialize))]
+pub enum DeviceState {
+	Unknown,
+	Sleep,
+	Standby,
+	/// Active or Idle
+	Active,
+	/// Executing SMART off-line or self-test
+	OfflineOrSelfTest,
+	Reserved(u8),
+	VendorSpecific(u8),
+}
+
+impl DeviceState {
+	fn from(state: u8) -> Self {
+		use self::DeviceState::*;
+		match state & 0x0f {
+			0 => Unknown,
+			1 => Sleep,
+			2 => Standby,
+			3 => Active,
+			4 => OfflineOrSelfTest,
+			x @ 5 ... 0xa => Reserved(x),
+			x => VendorSpecific(x),
+		}
+	}
+}
+
+/// Command (or reset) that preceded the error
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serializable", derive(Serialize))]
+pub struct Command {
+	pub device_control: u8,
+	pub features: u16,
+	pub count: u16,
+	pub lba: u64,
+	pub device: u8,
+	pub command: u8,
+	/// Milliseconds since power-on; wraps around
+	pub timestamp: u32,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serializable", derive(Serialize))]
+pub struct ErrorRecord {
+	/// Commands that preceded the error, oldest first; the last one is the command that caused the error
+	pub commands: Vec<Command>,
+
+	pub error: u8,
+	pub status: u8,
+	pub count: u16,
+	pub lba: u64,
+	pub device: u8,
+	/// Vendor-specific extended error information
+	pub extended: Vec<u8>,
+
+	pub state: DeviceState,
+	/// Power-on hours at the time the error occurred (saturated, i.e. `0xffff` really means `>= 0xffff`)
+	pub power_on_hours: u16,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serializable", derive(Serialize))]
+pub struct ErrorLog {
+	/// Total number of errors reported by the device, including those that are no longer in the log
+	pub error_count: u16,
+	/// Errors that are still in the log, newest first
+	pub errors: Vec<ErrorRecord>,
+}
+
+// merges lba low/mid/high with device register (the latter carries LBA (27:24) if LBA bit is set)
+fn lba28(low: u8, mid: u8, high: u8, device: u8) -> u64 {
+	let lba = low as u64 | (mid as u64) << 8 | (high as u64) << 16;
+	if device & 0x40 != 0 {
+		lba | (device as u64 & 0x0f) << 24
+	} else {
+		lba
+	}
+}
+
+// reads 48-bit LBA, laid out as 7:0, 31:24, 15:8, 39:32, 23:16, 47:40
+fn lba48(d: &[u8]) -> u64 {
+	d[0] as u64
+		| (d[2] as u64) << 8
+		| (d[4] as u64) << 16
+		| (d[1] as u64) << 24
+		| (d[3] as u64) << 32
+		| (d[5] as u64) << 40
+}
+
+/// Reads circular buffer of error log data structures, newest first; `index` is 1-based index of the most recent one (0 means the log is empty).
+fn newest_first<'a>(structures: &[&'a [u8]], index: usize) -> Vec<&'a [u8]> {
+	if index == 0 || index > structures.len() {
+		return vec![];
+	}
+
+	(0 .. index).rev().chain((index .. structures.len()).rev())
+		.map(|i| structures[i])
+		// unused data structures are filled with zeroes
+		.filter(|s| s.iter().any(|&x| x != 0))
+		.collect()
+}
+
+fn parse_record(s: &[u8]) -> ErrorRecord {
+	let commands = s[0..60].chunks(12)
+		.filter(|c| c.iter().any(|&x| x != 0))
+		.map(|c| Command {
+			device_control: c[0],
+			features: c[1] as u16,
+			count: c[2] as u16,
+			lba: lba28(c[3], c[4], c[5], c[6]),
+			device: c[6],
+			command: c[7],
+			timestamp: LittleEndian::read_u32(&c[8..12]),
+		})
+		.collect();
+
+	let e = &s[60..90];
+	ErrorRecord {
+		commands,
+		error: e[1],
+		status: e[7],
+		count: e[2] as u16,
+		lba: lba28(e[3], e[4], e[5], e[6]),
+		device: e[6],
+		extended: e[8..27].to_vec(),
+		state: DeviceState::from(e[27]),
+		power_on_hours: LittleEndian::read_u16(&e[28..30]),
+	}
+}
+
+fn parse_ext_record(s: &[u8]) -> ErrorRecord {
+	let commands = s[0..90].chunks(18)
+		.filter(|c| c.iter().any(|&x| x != 0))
+		.map(|c| Command {
+			device_control: c[0],
+			features: LittleEndian::read_u16(&c[1..3]),
+			count: LittleEndian::read_u16(&c[3..5]),
+			lba: lba48(&c[5..11]),
+			device: c[11],
+			command: c[12],
+			timestamp: LittleEndian::read_u32(&c[14..18]),
+		})
+		.collect();
+
+	let e = &s[90..124];
+	ErrorRecord {
+		commands,
+		error: e[1],
+		status: e[11],
+		count: LittleEndian::read_u16(&e[2..4]),
+		lba: lba48(&e[4..10]),
+		device: e[10],
+		extended: e[12..31].to_vec(),
+		state: DeviceState::from(e[31]),
+		power_on_hours: LittleEndian::read_u16(&e[32..34]),
+	}
+}
+
+/**
+Parses summary SMART error log (log address 01h) or comprehensive SMART error log (log address 02h); the latter might span multiple 512-byte pages.
+
+Returns `None` if `data` is too short.
+*/
+pub fn parse_error_log(data: &[u8]) -> Option<ErrorLog> {
+	if data.len() < 512 {
+		return None;
+	}
+
+	// each page holds 5 error log data structures, 90 bytes each
+	let structures: Vec<_> = data.chunks(512)
+		.filter(|page| page.len() == 512)
+		.flat_map(|page| page[2 .. 2 + 5*90].chunks(90))
+		.collect();
+
+	Some(ErrorLog {
+		error_count: LittleEndian::read_u16(&data[452..454]),
+		errors: newest_first(&structures, data[1] as usize).into_iter()
+			.map(parse_record)
+			.collect(),
+	})
+}
+
+/// Parses extended comprehensive SMART error log (GPL log address 03h), which may span multiple 512-byte pages; returns `None` if `data` is too short.
+pub fn parse_ext_error_log(data: &[u8]) -> Option<ErrorLog> {
+	if data.len() < 512 {
+		return None;
+	}
+
+	// each page holds 4 error log data structures, 124 bytes each
+	let structures: Vec<_> = data.chunks(512)
+		.filter(|page| page.len() == 512)
+		.flat_map(|page| page[4 .. 4 + 4*124].chunks(124))
+		.collect();
+
+	Some(ErrorLog {
+		error_count: LittleEndian::read_u16(&data[500..502]),
+		errors: newest_first(&structures, LittleEndian::read_u16(&data[2..4]) as usize).into_iter()
+			.map(parse_ext_record)
+			.collect(),
+	})
+}
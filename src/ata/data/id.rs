@@ -52,6 +52,17 @@ pub struct IdCommands {
 	pub read_write_dma_ext_gpl: bool,
 }
 
+/// SCT Command Transport capabilities
+#[derive(Debug)]
+#[cfg_attr(feature = "serializable", derive(Serialize))]
+pub struct IdSCT {
+	pub supported: bool,
+	pub write_same: bool,
+	pub error_recovery_control: bool,
+	pub feature_control: bool,
+	pub data_tables: bool,
+}
+
 #[derive(Debug)]
 #[cfg_attr(feature = "serializable", derive(Serialize))]
 pub struct Id {
@@ -87,6 +98,8 @@ pub struct Id {
 	pub smart: Ternary,
 	pub smart_error_logging_supported: bool,
 	pub smart_self_test_supported: bool,
+
+	pub sct: IdSCT,
 }
 
 fn is_set(word: u16, bit: usize) -> bool {
@@ -370,5 +383,13 @@ pub fn parse_id(data: &Vec<u8>) -> Id {
 
 		smart_error_logging_supported: is_set(data[84], 0), // XXX mirrored; see commands_supported
 		smart_self_test_supported: is_set(data[84], 1), // XXX mirrored; see commands_supported
+
+		sct: IdSCT {
+			supported: is_set(data[206], 0),
+			write_same: is_set(data[206], 2),
+			error_recovery_control: is_set(data[206], 3),
+			feature_control: is_set(data[206], 4),
+			data_tables: is_set(data[206], 5),
+		},
 	}
 }
@@ -0,0 +1,75 @@
+/*!
+Log directories: General Purpose Log directory (GPL log address 00h) and SMART log directory (SMART log address 00h), as seen in ATA8-ACS, A.2.
+*/
+
+use byteorder::{ByteOrder, LittleEndian};
+
+/// Which set of commands the log directory (and the logs it lists) is accessed with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serializable", derive(Serialize))]
+pub enum Source {
+	/// READ LOG EXT, READ LOG DMA EXT
+	GPL,
+	/// SMART READ LOG
+	SMART,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serializable", derive(Serialize))]
+pub struct Log {
+	pub address: u8,
+	/// Number of 512-byte pages in the log
+	pub pages: u16,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serializable", derive(Serialize))]
+pub struct LogDirectory {
+	pub source: Source,
+	/// Logging version; should be `0x0001`
+	pub version: u16,
+	/// Logs that the device implements (i.e. those that have at least one page), ordered by address
+	pub logs: Vec<Log>,
+}
+
+impl LogDirectory {
+	/// Returns number of pages of the log at address `address`, or `None` if the device does not implement that log.
+	pub fn pages(&self, address: u8) -> Option<u16> {
+		self.logs.iter()
+			.find(|log| log.address == address)
+			.map(|log| log.pages)
+	}
+
+	pub fn contains(&self, address: u8) -> bool {
+		self.pages(address).is_some()
+	}
+}
+
+/// Parses log directory (log address 00h) read with commands from the given `source`; returns `None` if `data` is too short.
+pub fn parse_log_directory(source: Source, data: &[u8]) -> Option<LogDirectory> {
+	if data.len() < 512 {
+		return None;
+	}
+
+	let logs = (1 .. 256)
+		.map(|address: usize| {
+			let offset = address * 2;
+			let pages = LittleEndian::read_u16(&data[offset .. offset + 2]);
+			Log {
+				address: address as u8,
+				pages: match source {
+					Source::GPL => pages,
+					// SMART READ LOG can only transfer up to 255 pages, and the upper byte of every entry is reserved
+					Source::SMART => pages & 0xff,
+				},
+			}
+		})
+		.filter(|log| log.pages != 0)
+		.collect();
+
+	Some(LogDirectory {
+		source,
+		version: LittleEndian::read_u16(&data[0..2]),
+		logs,
+	})
+}
@@ -1,3 +1,15 @@
 pub mod attr;
 pub mod id;
 pub mod health;
+pub mod self_test;
+pub mod error_log;
+pub mod log_dir;
+pub mod device_statistics;
+pub mod phy_events;
+pub mod sct;
+pub mod smart_data;
+
+/// Sums all the bytes of a data structure, wrapping on overflow; for structures that end with a checksum, the sum including it is zero.
+pub(crate) fn byte_sum(data: &[u8]) -> u8 {
+	data.iter().fold(0u8, |sum, &x| sum.wrapping_add(x))
+}
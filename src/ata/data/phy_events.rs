@@ -0,0 +1,161 @@
+/*!
+SATA Phy Event Counters log (GPL log address 11h), as seen in SATA 3.2, 13.7.5.
+
+Non-zero counters of CRC errors or `R_ERR` responses usually point to a bad cable or backplane rather than to the drive itself.
+*/
+
+use byteorder::{ByteOrder, LittleEndian};
+
+use ata::data::byte_sum;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serializable", derive(Serialize))]
+pub enum Counter {
+	/// Command failed due to ICRC error
+	ICRCErrors,
+	/// R_ERR response for data FIS
+	DataFISErrors,
+	/// R_ERR response for device-to-host data FIS
+	D2HDataFISErrors,
+	/// R_ERR response for host-to-device data FIS
+	H2DDataFISErrors,
+	/// R_ERR response for non-data FIS
+	NonDataFISErrors,
+	/// R_ERR response for device-to-host non-data FIS
+	D2HNonDataFISErrors,
+	/// R_ERR response for host-to-device non-data FIS
+	H2DNonDataFISErrors,
+	/// Device-to-host non-data FIS retries
+	D2HNonDataFISRetries,
+	/// Transition from drive PhyRdy to drive PhyNRdy
+	PhyRdyToPhyNRdy,
+	/// Device-to-host register FISes sent due to a COMRESET
+	RegisterFISComreset,
+	/// CRC errors within host-to-device FIS
+	H2DCRCErrors,
+	/// Non-CRC errors within host-to-device FIS
+	H2DNonCRCErrors,
+	/// R_ERR response for host-to-device data FIS due to CRC errors
+	H2DDataFISCRCErrors,
+	/// R_ERR response for host-to-device data FIS due to non-CRC errors
+	H2DDataFISNonCRCErrors,
+	/// R_ERR response for host-to-device non-data FIS due to CRC errors
+	H2DNonDataFISCRCErrors,
+	/// R_ERR response for host-to-device non-data FIS due to non-CRC errors
+	H2DNonDataFISNonCRCErrors,
+	VendorSpecific(u16),
+	Reserved(u16),
+}
+
+impl Counter {
+	fn from(id: u16) -> Self {
+		use self::Counter::*;
+		if id & 0x8000 != 0 {
+			return VendorSpecific(id & 0x0fff);
+		}
+		match id & 0x0fff {
+			0x001 => ICRCErrors,
+			0x002 => DataFISErrors,
+			0x003 => D2HDataFISErrors,
+			0x004 => H2DDataFISErrors,
+			0x005 => NonDataFISErrors,
+			0x006 => D2HNonDataFISErrors,
+			0x007 => H2DNonDataFISErrors,
+			0x008 => D2HNonDataFISRetries,
+			0x009 => PhyRdyToPhyNRdy,
+			0x00a => RegisterFISComreset,
+			0x00b => H2DCRCErrors,
+			0x00d => H2DNonCRCErrors,
+			0x00f => H2DDataFISCRCErrors,
+			0x010 => H2DDataFISNonCRCErrors,
+			0x012 => H2DNonDataFISCRCErrors,
+			0x013 => H2DNonDataFISNonCRCErrors,
+			x => Reserved(x),
+		}
+	}
+
+	/// Returns the name of the counter, suitable for use in labels and keys; `None` for vendor-specific and reserved counters.
+	pub fn name(&self) -> Option<&'static str> {
+		use self::Counter::*;
+		Some(match *self {
+			ICRCErrors => "icrc-errors",
+			DataFISErrors => "data-fis-r-err",
+			D2HDataFISErrors => "d2h-data-fis-r-err",
+			H2DDataFISErrors => "h2d-data-fis-r-err",
+			NonDataFISErrors => "non-data-fis-r-err",
+			D2HNonDataFISErrors => "d2h-non-data-fis-r-err",
+			H2DNonDataFISErrors => "h2d-non-data-fis-r-err",
+			D2HNonDataFISRetries => "d2h-non-data-fis-retries",
+			PhyRdyToPhyNRdy => "phy-rdy-to-phy-nrdy",
+			RegisterFISComreset => "d2h-register-fis-comreset",
+			H2DCRCErrors => "h2d-fis-crc-errors",
+			H2DNonCRCErrors => "h2d-fis-non-crc-errors",
+			H2DDataFISCRCErrors => "h2d-data-fis-r-err-crc",
+			H2DDataFISNonCRCErrors => "h2d-data-fis-r-err-non-crc",
+			H2DNonDataFISCRCErrors => "h2d-non-data-fis-r-err-crc",
+			H2DNonDataFISNonCRCErrors => "h2d-non-data-fis-r-err-non-crc",
+			VendorSpecific(_) | Reserved(_) => return None,
+		})
+	}
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serializable", derive(Serialize))]
+pub struct PhyEventCounter {
+	pub counter: Counter,
+	/// Raw counter identifier, as reported by the device
+	pub id: u16,
+	/// Width of the counter, in bits
+	pub width: u8,
+	pub value: u64,
+	/// Counters do not wrap around, and stop at their maximum value instead
+	pub saturated: bool,
+}
+
+/**
+Parses SATA Phy Event Counters log; returns `None` if `data` is too short.
+
+Counters that run past the end of the page are discarded. Checksum mismatch is only reported as a warning, for some devices are known to get it wrong.
+*/
+pub fn parse_phy_event_counters(data: &[u8]) -> Option<Vec<PhyEventCounter>> {
+	if data.len() < 512 {
+		return None;
+	}
+
+	// sum of all the bytes, including the checksum, should be zero
+	if byte_sum(&data[..512]) != 0 {
+		warn!("SATA Phy Event Counters log checksum mismatch");
+	}
+
+	let mut counters = vec![];
+	// bytes 0-3 are reserved, the last byte is the checksum
+	let mut offset = 4;
+	while offset + 2 <= 511 {
+		let id = LittleEndian::read_u16(&data[offset .. offset + 2]);
+		if id == 0 {
+			// end of the list
+			break;
+		}
+
+		// bits 14:12 is counter length, in 16-bit words
+		let len = ((id >> 12) & 0x7) as usize * 2;
+		offset += 2;
+		if len == 0 || len > 8 || offset + len > 511 {
+			warn!("malformed SATA Phy Event Counter {:#06x}, ignoring the rest of the log", id);
+			break;
+		}
+
+		let value = LittleEndian::read_uint(&data[offset .. offset + len], len);
+		let width = len as u8 * 8;
+		counters.push(PhyEventCounter {
+			counter: Counter::from(id),
+			id: id & 0x8fff,
+			width,
+			value,
+			saturated: value == (!0u64 >> (64 - width)),
+		});
+		offset += len;
+	}
+
+	Some(counters)
+}
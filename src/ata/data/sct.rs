@@ -0,0 +1,188 @@
+/*!
+SCT Command Transport: key pages for SCT commands and parsers of their replies, as seen in ACS-3, 8.
+
+SCT commands are issued by writing a key page to log address E0h, then data (if any) is transferred through log address E1h; SCT status is read from log address E0h.
+*/
+
+use byteorder::{ByteOrder, LittleEndian};
+
+/// SCT action codes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+	WriteSame = 2,
+	ErrorRecoveryControl = 3,
+	FeatureControl = 4,
+	DataTables = 5,
+}
+
+/// Composes SCT command key page with given action code, function code and the parameters that follow them (e.g. selection code, value).
+pub fn key_page(action: Action, function: u16, params: &[u16]) -> Vec<u8> {
+	let mut data = vec![0; 512];
+	LittleEndian::write_u16(&mut data[0..2], action as u16);
+	LittleEndian::write_u16(&mut data[2..4], function);
+	for (i, param) in params.iter().enumerate() {
+		let offset = 4 + i*2;
+		LittleEndian::write_u16(&mut data[offset .. offset + 2], *param);
+	}
+	data
+}
+
+// temperatures are signed bytes, 0x80 is reported if the value is not valid
+fn temperature(t: u8) -> Option<i8> {
+	match t {
+		0x80 => None,
+		t => Some(t as i8),
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serializable", derive(Serialize))]
+pub enum DeviceState {
+	/// Active, waiting for a command
+	Active,
+	Standby,
+	Sleep,
+	/// Executing device self-test in background
+	SelfTest,
+	/// Executing SMART off-line data collection in background
+	OfflineDataCollection,
+	/// Executing SCT command in background
+	SCTCommand,
+	Reserved(u8),
+}
+
+impl DeviceState {
+	fn from(state: u8) -> Self {
+		use self::DeviceState::*;
+		match state {
+			0 => Active,
+			1 => Standby,
+			2 => Sleep,
+			3 => SelfTest,
+			4 => OfflineDataCollection,
+			5 => SCTCommand,
+			x => Reserved(x),
+		}
+	}
+}
+
+/// All temperatures are in °C; `None` means the device does not report that value.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serializable", derive(Serialize))]
+pub struct SCTStatus {
+	pub format_version: u16,
+	/// Vendor-specific version of SCT implementation
+	pub sct_version: u16,
+	pub spec_version: u16,
+	pub device_state: DeviceState,
+
+	/// Extended status code of the last SCT command (`0xffff` means the command is still in progress)
+	pub extended_status: u16,
+	/// Action code of the last SCT command
+	pub action: u16,
+	/// Function code of the last SCT command
+	pub function: u16,
+	/// Current (or last) LBA of the SCT command executed in background
+	pub lba: u64,
+
+	pub temperature: Option<i8>,
+	/// Minimum temperature since power-on
+	pub min_temperature: Option<i8>,
+	/// Maximum temperature since power-on
+	pub max_temperature: Option<i8>,
+	pub lifetime_min_temperature: Option<i8>,
+	pub lifetime_max_temperature: Option<i8>,
+	/// Number of minutes over the temperature limit
+	pub over_limit_count: u32,
+	/// Number of minutes under the temperature limit
+	pub under_limit_count: u32,
+}
+
+/// Parses SCT status (read from log address E0h); returns `None` if `data` is too short.
+pub fn parse_sct_status(data: &[u8]) -> Option<SCTStatus> {
+	if data.len() < 512 {
+		return None;
+	}
+
+	Some(SCTStatus {
+		format_version: LittleEndian::read_u16(&data[0..2]),
+		sct_version: LittleEndian::read_u16(&data[2..4]),
+		spec_version: LittleEndian::read_u16(&data[4..6]),
+		device_state: DeviceState::from(data[10]),
+
+		extended_status: LittleEndian::read_u16(&data[14..16]),
+		action: LittleEndian::read_u16(&data[16..18]),
+		function: LittleEndian::read_u16(&data[18..20]),
+		lba: LittleEndian::read_u64(&data[40..48]),
+
+		temperature: temperature(data[200]),
+		min_temperature: temperature(data[201]),
+		max_temperature: temperature(data[202]),
+		lifetime_min_temperature: temperature(data[203]),
+		lifetime_max_temperature: temperature(data[204]),
+		over_limit_count: LittleEndian::read_u32(&data[206..210]),
+		under_limit_count: LittleEndian::read_u32(&data[210..214]),
+	})
+}
+
+/// Data table that holds HDA temperature history
+pub const TEMPERATURE_HISTORY_TABLE: u16 = 2;
+
+/// HDA temperature history; all temperatures are in °C, `None` means the value is not available.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serializable", derive(Serialize))]
+pub struct TemperatureHistory {
+	pub format_version: u16,
+	/// How often the temperature is sampled, in minutes
+	pub sampling_period: u16,
+	/// Time between history entries, in minutes; every entry is a maximum of the samples taken during that time
+	pub interval: u16,
+
+	pub max_operating_temperature: Option<i8>,
+	pub over_limit_temperature: Option<i8>,
+	pub min_operating_temperature: Option<i8>,
+	pub under_limit_temperature: Option<i8>,
+
+	/// History entries, oldest first
+	pub history: Vec<Option<i8>>,
+}
+
+/// Parses HDA temperature history data table (read from log address E1h); returns `None` if `data` is too short.
+pub fn parse_temperature_history(data: &[u8]) -> Option<TemperatureHistory> {
+	if data.len() < 512 {
+		return None;
+	}
+
+	// circular buffer: size is the number of entries, index is the most recent one
+	let size = (LittleEndian::read_u16(&data[30..32]) as usize).min(512 - 34);
+	let index = LittleEndian::read_u16(&data[32..34]) as usize;
+	let entries = &data[34 .. 34 + size];
+
+	let history = if index < size {
+		entries[index + 1 ..].iter().chain(entries[.. index + 1].iter())
+			.map(|&t| temperature(t))
+			.collect()
+	} else {
+		vec![]
+	};
+
+	Some(TemperatureHistory {
+		format_version: LittleEndian::read_u16(&data[0..2]),
+		sampling_period: LittleEndian::read_u16(&data[2..4]),
+		interval: LittleEndian::read_u16(&data[4..6]),
+
+		max_operating_temperature: temperature(data[6]),
+		over_limit_temperature: temperature(data[7]),
+		min_operating_temperature: temperature(data[8]),
+		under_limit_temperature: temperature(data[9]),
+
+		history,
+	})
+}
+
+/// Error Recovery Control timers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ERCTimer {
+	Read = 1,
+	Write = 2,
+}
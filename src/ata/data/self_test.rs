@@ -0,0 +1,227 @@
+/*!
+SMART self-tests: execution status (as reported in SMART READ DATA), self-test logs, and selective self-test log.
+*/
+
+use byteorder::{ByteOrder, LittleEndian};
+
+use ata::data::byte_sum;
+
+/// Status of the last (or current) self-test, as seen in ATA8-ACS, table 56 (and self-test log entries)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serializable", derive(Serialize))]
+pub enum SelfTestStatus {
+	/// Previous self-test completed without error, or no self-test has ever been run
+	NoError,
+	AbortedByHost,
+	/// Interrupted by the host with a hardware or software reset
+	Interrupted,
+	/// Fatal error or unknown test error occurred that prevented the device from completing the test
+	FatalError,
+	/// Completed with a failure of unknown test element
+	UnknownFailure,
+	ElectricalFailure,
+	ServoFailure,
+	ReadFailure,
+	/// Completed with a failure that is suspected to be caused by handling damage
+	HandlingDamage,
+	InProgress,
+	Reserved(u8),
+}
+
+impl SelfTestStatus {
+	/// Decodes upper 4 bits of the status byte.
+	pub fn from(status: u8) -> Self {
+		use self::SelfTestStatus::*;
+		match status >> 4 {
+			0 => NoError,
+			1 => AbortedByHost,
+			2 => Interrupted,
+			3 => FatalError,
+			4 => UnknownFailure,
+			5 => ElectricalFailure,
+			6 => ServoFailure,
+			7 => ReadFailure,
+			8 => HandlingDamage,
+			15 => InProgress,
+			x => Reserved(x),
+		}
+	}
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serializable", derive(Serialize))]
+pub struct SelfTestProgress {
+	pub status: SelfTestStatus,
+	/// Approximate percentage of the test remaining (in steps of 10%); only makes sense if the test is in progress
+	pub remaining: u8,
+
+	/// Recommended polling time for the short self-test, in minutes; `None` if self-tests are not supported
+	pub short_time: Option<u16>,
+	/// Recommended polling time for the extended self-test, in minutes; `None` if self-tests are not supported
+	pub extended_time: Option<u16>,
+	/// Recommended polling time for the conveyance self-test, in minutes; `None` if conveyance self-test is not supported
+	pub conveyance_time: Option<u16>,
+
+	pub selective: bool,
+}
+
+/// Parses self-test execution status and polling times from SMART READ DATA reply; returns `None` if `data` is too short.
+pub fn parse_self_test_progress(data: &[u8]) -> Option<SelfTestProgress> {
+	if data.len() < 512 {
+		return None;
+	}
+
+	// off-line data collection capability
+	let self_test = data[367] & (1<<4) != 0;
+	let conveyance = data[367] & (1<<5) != 0;
+	let selective = data[367] & (1<<6) != 0;
+
+	Some(SelfTestProgress {
+		status: SelfTestStatus::from(data[363]),
+		remaining: (data[363] & 0xf) * 10,
+
+		short_time: if self_test { Some(data[372] as u16) } else { None },
+		extended_time: if ! self_test { None } else {
+			match data[373] {
+				// extended self-test might take more than 254 minutes on modern drives
+				0xff => Some(LittleEndian::read_u16(&data[375..377])),
+				x => Some(x as u16),
+			}
+		},
+		conveyance_time: if conveyance { Some(data[374] as u16) } else { None },
+
+		selective,
+	})
+}
+
+/// Maximum number of LBA spans the selective self-test can cover
+pub const SELECTIVE_SPANS: usize = 5;
+
+/**
+Composes selective self-test log (log address 09h) with given `(first, last)` LBA spans, ready to be written with SMART WRITE LOG.
+
+Returns `None` if there are more than [`SELECTIVE_SPANS`](constant.SELECTIVE_SPANS.html) spans.
+*/
+pub fn selective_self_test_log(spans: &[(u64, u64)]) -> Option<Vec<u8>> {
+	if spans.len() > SELECTIVE_SPANS {
+		return None;
+	}
+
+	let mut data = vec![0; 512];
+	LittleEndian::write_u16(&mut data[0..2], 1); // data structure revision number
+
+	for (i, (first, last)) in spans.iter().enumerate() {
+		let offset = 2 + i * 16;
+		LittleEndian::write_u64(&mut data[offset .. offset + 8], *first);
+		LittleEndian::write_u64(&mut data[offset + 8 .. offset + 16], *last);
+	}
+
+	// sum of all the bytes, including the checksum, should be zero
+	data[511] = 0u8.wrapping_sub(byte_sum(&data));
+
+	Some(data)
+}
+
+/// Self-test routine, as it was requested in SMART EXECUTE OFF-LINE IMMEDIATE
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serializable", derive(Serialize))]
+pub enum SelfTestType {
+	/// Off-line data collection routine
+	Offline,
+	Short,
+	Extended,
+	Conveyance,
+	Selective,
+	VendorSpecific(u8),
+	Reserved(u8),
+}
+
+/// Single entry of SMART self-test log or of extended SMART self-test log
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serializable", derive(Serialize))]
+pub struct SelfTestLogEntry {
+	pub test: SelfTestType,
+	/// Whether the test was run in captive mode
+	pub captive: bool,
+	pub status: SelfTestStatus,
+	/// Approximate percentage of the test remaining (in steps of 10%)
+	pub remaining: u8,
+	/// Power-on hours at the time the test was completed or aborted (saturated, i.e. `0xffff` really means `>= 0xffff`)
+	pub power_on_hours: u16,
+	/// Vendor-specific last test element, or segment, the test was at
+	pub checkpoint: u8,
+	/// LBA of the first failure; vendor-specific (usually either 0 or all ones) if the test did not fail
+	pub first_failure_lba: u64,
+}
+
+fn parse_test_type(lba_low: u8) -> (SelfTestType, bool) {
+	use self::SelfTestType::*;
+	let captive = lba_low & 0x80 != 0 && lba_low != 0xff;
+	let test = match lba_low {
+		0x00 => Offline,
+		0x01 | 0x81 => Short,
+		0x02 | 0x82 => Extended,
+		0x03 | 0x83 => Conveyance,
+		0x04 | 0x84 => Selective,
+		x @ 0x40 ... 0x7e | x @ 0x90 ... 0xff => VendorSpecific(x),
+		x => Reserved(x),
+	};
+	(test, captive)
+}
+
+/**
+Reads circular buffer of log descriptors, newest first.
+
+`index` is 1-based index of the most recent descriptor (0 means there are no descriptors), `lba_len` is the length of the failing LBA field.
+*/
+fn parse_descriptors(descriptors: &[&[u8]], index: usize, lba_len: usize) -> Vec<SelfTestLogEntry> {
+	if index == 0 || index > descriptors.len() {
+		return vec![];
+	}
+
+	// index-1, index-2, …, 0, len-1, …, index
+	let order = (0 .. index).rev().chain((index .. descriptors.len()).rev());
+
+	order
+		.map(|i| descriptors[i])
+		// unused descriptors are filled with zeroes
+		.filter(|d| d.iter().any(|&x| x != 0))
+		.map(|d| {
+			let (test, captive) = parse_test_type(d[0]);
+			SelfTestLogEntry {
+				test,
+				captive,
+				status: SelfTestStatus::from(d[1]),
+				remaining: (d[1] & 0xf) * 10,
+				power_on_hours: LittleEndian::read_u16(&d[2..4]),
+				checkpoint: d[4],
+				first_failure_lba: LittleEndian::read_uint(&d[5 .. 5 + lba_len], lba_len),
+			}
+		})
+		.collect()
+}
+
+/// Parses SMART self-test log (log address 06h), returning entries newest first; returns `None` if `data` is too short.
+pub fn parse_self_test_log(data: &[u8]) -> Option<Vec<SelfTestLogEntry>> {
+	if data.len() < 512 {
+		return None;
+	}
+
+	// 21 descriptors, 24 bytes each
+	let descriptors: Vec<_> = data[2 .. 2 + 21*24].chunks(24).collect();
+	Some(parse_descriptors(&descriptors, data[508] as usize, 4))
+}
+
+/// Parses extended SMART self-test log (GPL log address 07h), which may span multiple 512-byte pages, returning entries newest first; returns `None` if `data` is too short.
+pub fn parse_ext_self_test_log(data: &[u8]) -> Option<Vec<SelfTestLogEntry>> {
+	if data.len() < 512 {
+		return None;
+	}
+
+	// each page holds 19 descriptors, 26 bytes each
+	let descriptors: Vec<_> = data.chunks(512)
+		.filter(|page| page.len() == 512)
+		.flat_map(|page| page[4 .. 4 + 19*26].chunks(26))
+		.collect();
+	Some(parse_descriptors(&descriptors, LittleEndian::read_u16(&data[2..4]) as usize, 6))
+}
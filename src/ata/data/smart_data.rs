@@ -0,0 +1,128 @@
+/*!
+SMART READ DATA reply as a whole: attributes, off-line data collection and self-test status, capabilities, as seen in ATA8-ACS, 7.53.6.
+*/
+
+use byteorder::{ByteOrder, LittleEndian};
+
+use ata::data::byte_sum;
+use ata::data::attr::{self, SmartAttribute};
+use ata::data::self_test::{self, SelfTestProgress};
+use drivedb;
+
+/// Off-line data collection status
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serializable", derive(Serialize))]
+pub enum OfflineStatus {
+	NeverStarted,
+	CompletedWithoutError,
+	InProgress,
+	/// Suspended by an interrupting command from the host
+	Suspended,
+	/// Aborted by an interrupting command from the host
+	AbortedByHost,
+	/// Aborted by the device with a fatal error
+	AbortedByDevice,
+	VendorSpecific(u8),
+	Reserved(u8),
+}
+
+impl OfflineStatus {
+	fn from(status: u8) -> Self {
+		use self::OfflineStatus::*;
+		match status & 0x7f {
+			0x00 => NeverStarted,
+			0x02 => CompletedWithoutError,
+			0x03 => InProgress,
+			0x04 => Suspended,
+			0x05 => AbortedByHost,
+			0x06 => AbortedByDevice,
+			x @ 0x40 ... 0x7f => VendorSpecific(x),
+			x => Reserved(x),
+		}
+	}
+}
+
+/// Off-line data collection capabilities
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serializable", derive(Serialize))]
+pub struct OfflineCapabilities {
+	/// SMART EXECUTE OFF-LINE IMMEDIATE is supported
+	pub execute_offline_immediate: bool,
+	/// Off-line data collection is aborted (rather than suspended) upon receipt of a new command
+	pub abort_on_new_command: bool,
+	pub offline_read_scanning: bool,
+	pub self_test: bool,
+	pub conveyance_self_test: bool,
+	pub selective_self_test: bool,
+}
+
+#[derive(Debug)]
+#[cfg_attr(feature = "serializable", derive(Serialize))]
+pub struct SmartData {
+	pub revision: u16,
+	pub attributes: Vec<SmartAttribute>,
+
+	pub offline_status: OfflineStatus,
+	/// Automatic off-line data collection is enabled
+	pub auto_offline: bool,
+	/// Time to complete off-line data collection, in seconds
+	pub offline_time: u16,
+	pub offline_capabilities: OfflineCapabilities,
+
+	/// Self-test execution status and polling times
+	pub self_test: SelfTestProgress,
+
+	/// Device saves SMART data prior to going into a power saving mode
+	pub saves_data_before_power_saving: bool,
+	/// SMART ENABLE/DISABLE ATTRIBUTE AUTOSAVE is supported
+	pub attribute_autosave: bool,
+	pub error_logging: bool,
+
+	pub checksum: u8,
+	/// Whether the checksum of the SMART READ DATA reply matches
+	pub checksum_valid: bool,
+	/// Whether the checksum of the SMART READ THRESHOLDS reply matches
+	pub thresholds_checksum_valid: bool,
+}
+
+/// Checks whether the sum of all the bytes of 512-byte data structure, including the checksum in the last byte, is zero.
+pub fn checksum_valid(data: &[u8]) -> bool {
+	data.len() >= 512 && byte_sum(&data[..512]) == 0
+}
+
+/// Parses SMART READ DATA and SMART READ THRESHOLDS replies, rendering attributes using optional drivedb entry; returns `None` if either reply is too short.
+pub fn parse_smart_data(data: &Vec<u8>, raw_thresh: &Vec<u8>, meta: &Option<drivedb::DriveMeta>) -> Option<SmartData> {
+	if data.len() < 512 || raw_thresh.len() < 512 {
+		return None;
+	}
+
+	let capability = LittleEndian::read_u16(&data[368..370]);
+
+	Some(SmartData {
+		revision: LittleEndian::read_u16(&data[0..2]),
+		attributes: attr::parse_smart_values(data, raw_thresh, meta),
+
+		offline_status: OfflineStatus::from(data[362]),
+		auto_offline: data[362] & 0x80 != 0,
+		offline_time: LittleEndian::read_u16(&data[364..366]),
+		offline_capabilities: OfflineCapabilities {
+			execute_offline_immediate: data[367] & (1<<0) != 0,
+			// bit 1 is vendor-specific
+			abort_on_new_command: data[367] & (1<<2) != 0,
+			offline_read_scanning: data[367] & (1<<3) != 0,
+			self_test: data[367] & (1<<4) != 0,
+			conveyance_self_test: data[367] & (1<<5) != 0,
+			selective_self_test: data[367] & (1<<6) != 0,
+		},
+
+		self_test: self_test::parse_self_test_progress(data)?,
+
+		saves_data_before_power_saving: capability & (1<<0) != 0,
+		attribute_autosave: capability & (1<<1) != 0,
+		error_logging: data[370] & 1 != 0,
+
+		checksum: data[511],
+		checksum_valid: checksum_valid(data),
+		thresholds_checksum_valid: checksum_valid(raw_thresh),
+	})
+}
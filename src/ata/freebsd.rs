@@ -1,78 +1,103 @@
 use std::mem;
+use std::time::Duration;
 
 use cam::{*, bindings::*};
 
 use ata;
-use ata::ATADevice;
+use ata::ATATransport;
 use Direction;
 use Device;
+use scsi::ATAError;
 
-use std::io;
+impl Device {
+	/// Executes ATA command through CAM; with `extend` unset, only lower 8 bits of each register are used.
+	fn ata_io(&self, proto: ata::Protocol, dir: Direction, extend: bool, regs: &ata::RegistersWriteExt, data_out: &[u8], timeout: Option<Duration>) -> Result<(ata::RegistersReadExt, Vec<u8>), ATAError> {
+		// see `ATATransport::do_ata_cmd` on how transfer length is derived from the request
+		let len = ata::transfer_len(dir, regs.sector_count as usize, data_out)?;
+		let (mut data, cam_dir) = match dir {
+			Direction::To => (data_out.to_vec(), ccb_flags_CAM_DIR_OUT),
+			Direction::From => (vec![0; len], ccb_flags_CAM_DIR_IN),
+			// Direction::Both is rejected by transfer_len()
+			_ => (vec![], ccb_flags_CAM_DIR_NONE),
+		};
 
-impl ATADevice<Device> {
-	ata_do!(io::Error);
-	fn ata_platform_do(&self, dir: Direction, regs: &ata::RegistersWrite) -> Result<(ata::RegistersRead, Vec<u8>), io::Error> {
-		let timeout = 10; // in seconds; TODO configurable
+		let ccb = CCB::new(&self.dev);
 
-		let mut data: [u8; 512] = [0; 512];
-
-		let ccb = CCB::new(&self.device.dev);
+		// previous register contents are ignored for 28-bit commands, but let's not send garbage anyway
+		let hob = |x: u64| if extend { x as u8 } else { 0 };
 
 		unsafe {
 			let h = ccb.ccb_h();
 			h.func_code = xpt_opcode_XPT_ATA_IO;
-			h.flags = {
-				use self::Direction::*;
-				match dir {
-					From => ccb_flags_CAM_DIR_IN,
-					To => unimplemented!(), //CAM_DIR_OUT,
-					Both => unimplemented!(), //CAM_DIR_BOTH,
-					None => ccb_flags_CAM_DIR_NONE,
-				}
-			};
+			h.flags = cam_dir;
 			h.retry_count = 0;
-			h.timeout = timeout * 1000;
+			h.timeout = self.timeout_ms(timeout);
 
 			let ataio = ccb.ataio();
 			ataio.data_ptr = data.as_mut_ptr();
-			ataio.dxfer_len = 512;
+			ataio.dxfer_len = data.len() as u32;
 			ataio.ata_flags = 0;
 
 			ataio.cmd.command	= regs.command;
-			ataio.cmd.features	= regs.features;
-			ataio.cmd.lba_low_exp	= 0;
-			ataio.cmd.lba_low	= regs.sector;
-			ataio.cmd.lba_mid_exp	= 0;
-			ataio.cmd.lba_mid	= regs.cyl_low;
-			ataio.cmd.lba_high_exp	= 0;
-			ataio.cmd.lba_high	= regs.cyl_high;
+			ataio.cmd.features	= regs.features as u8;
+			ataio.cmd.features_exp	= hob(regs.features as u64 >> 8);
+			ataio.cmd.lba_low_exp	= hob(regs.lba >> 24);
+			ataio.cmd.lba_low	= regs.lba as u8;
+			ataio.cmd.lba_mid_exp	= hob(regs.lba >> 32);
+			ataio.cmd.lba_mid	= (regs.lba >> 8) as u8;
+			ataio.cmd.lba_high_exp	= hob(regs.lba >> 40);
+			ataio.cmd.lba_high	= (regs.lba >> 16) as u8;
 			ataio.cmd.device	= regs.device;
-			ataio.cmd.sector_count	= regs.sector_count;
+			ataio.cmd.sector_count	= regs.sector_count as u8;
+			ataio.cmd.sector_count_exp	= hob(regs.sector_count as u64 >> 8);
 
 			ataio.cmd.flags = (CAM_ATAIO_NEEDRESULT | CAM_ATAIO_48BIT) as u8;
+			// CAM does not care whether it's multiword DMA or UDMA
+			match (dir, proto) {
+				(Direction::None, _) | (_, ata::Protocol::PIO) => (),
+				_ => ataio.cmd.flags |= CAM_ATAIO_DMA as u8,
+			}
 
 			h.flags |= ccb_flags_CAM_DEV_QFRZDIS;
 		}
 
-		self.device.dev.send_ccb(&ccb)?;
+		self.dev.send_ccb(&ccb)?;
 
 		if ccb.get_status() != cam_status_CAM_REQ_CMP {
-			Err(error::from_status(&self.device.dev, &ccb))?
+			Err(error::from_status(&self.dev, &ccb))?
 		}
 
 		let ataio = unsafe { ccb.ataio() };
 
-		Ok((ata::RegistersRead {
+		let hob = |x: u8| if extend { x as u64 } else { 0 };
+		Ok((ata::RegistersReadExt {
 			error: ataio.res.error,
 
-			sector_count: ataio.res.sector_count,
+			sector_count: (hob(ataio.res.sector_count_exp) << 8 | ataio.res.sector_count as u64) as u16,
 
-			sector: ataio.res.lba_low,
-			cyl_low: ataio.res.lba_mid,
-			cyl_high: ataio.res.lba_high,
+			lba: ataio.res.lba_low as u64
+				| (ataio.res.lba_mid as u64) << 8
+				| (ataio.res.lba_high as u64) << 16
+				| hob(ataio.res.lba_low_exp) << 24
+				| hob(ataio.res.lba_mid_exp) << 32
+				| hob(ataio.res.lba_high_exp) << 40,
 			device: ataio.res.device,
 
 			status: ataio.res.status,
-		}, data.to_vec()))
+		}, match dir {
+			Direction::From => data,
+			_ => vec![],
+		}))
+	}
+}
+
+impl ATATransport for Device {
+	fn do_ata_cmd(&self, proto: ata::Protocol, dir: Direction, regs: &ata::RegistersWrite, data_out: &[u8], timeout: Option<Duration>) -> Result<(ata::RegistersRead, Vec<u8>), ATAError> {
+		self.ata_io(proto, dir, false, &regs.into(), data_out, timeout)
+			.map(|(regs, data)| (regs.into(), data))
+	}
+
+	fn do_ata_cmd_ext(&self, proto: ata::Protocol, dir: Direction, regs: &ata::RegistersWriteExt, data_out: &[u8], timeout: Option<Duration>) -> Result<(ata::RegistersReadExt, Vec<u8>), ATAError> {
+		self.ata_io(proto, dir, true, regs, data_out, timeout)
 	}
 }
@@ -32,16 +32,14 @@ match id.smart {
 
 use Direction;
 
-#[cfg(not(target_os = "linux"))]
-use Device;
+use ata::{ATADevice, ATATransport, RegistersRead, RegistersWrite, RegistersReadExt, RegistersWriteExt, Command, SMARTFeature, Protocol};
+use scsi;
 
-use ata::{ATADevice, RegistersRead, RegistersWrite, Command, SMARTFeature};
-use scsi::{self, SCSIDevice};
-
-use ata::data::{id, health, attr};
+use ata::data::{id, health, attr, self_test, error_log, log_dir, device_statistics, phy_events, sct, smart_data};
 use drivedb;
 
 use std::io;
+use std::time::Duration;
 
 quick_error! {
 	#[derive(Debug)]
@@ -57,6 +55,50 @@ quick_error! {
 			from()
 			display("{}", err)
 		}
+		/// Device reported an error (ERR bit is set in the status register)
+		Command(error: u8) {
+			description("ATA command failed")
+			display("ATA command failed: error register is {:#04x}", error)
+		}
+		InvalidArgument(what: &'static str) {
+			display("Invalid argument: {}", what)
+		}
+		InvalidData(what: &'static str) {
+			display("Unable to {}", what)
+		}
+	}
+}
+
+/// Self-tests that can be started with [`Misc::start_self_test`](trait.Misc.html#method.start_self_test)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SelfTest {
+	Short = 1,
+	Extended = 2,
+	Conveyance = 3,
+	/// Tests LBA spans that were set beforehand; see [`Misc::start_selective_self_test`](trait.Misc.html#method.start_selective_self_test)
+	Selective = 4,
+}
+
+fn read_log_regs(command: Command, features: u16, log: u8, page: u16, pages: u16) -> RegistersWriteExt {
+	RegistersWriteExt {
+		command: command as u8,
+		features,
+		sector_count: pages,
+		// LBA (39:32) is page number (15:8), LBA (15:8) is page number (7:0), and LBA (7:0) is log address
+		lba: (page as u64 >> 8) << 32 | (page as u64 & 0xff) << 8 | log as u64,
+		device: 0,
+	}
+}
+
+fn check_status(regs: &RegistersRead) -> Result<(), Error> {
+	check_status_bits(regs.status, regs.error)
+}
+
+fn check_status_bits(status: u8, error: u8) -> Result<(), Error> {
+	if status & 1 != 0 {
+		Err(Error::Command(error))
+	} else {
+		Ok(())
 	}
 }
 
@@ -64,6 +106,12 @@ quick_error! {
 pub trait Misc {
 	// This one not only invokes ata_do() from `ATADevice<Whatever>`, but also converts into our own error type.
 	fn ata_do(&self, dir: Direction, regs: &RegistersWrite) -> Result<(RegistersRead, Vec<u8>), Error>;
+	// Same for ata_transfer().
+	fn ata_transfer(&self, proto: Protocol, dir: Direction, regs: &RegistersWrite, data_out: &[u8], timeout: Option<Duration>) -> Result<(RegistersRead, Vec<u8>), Error>;
+	// Same for ata_do_ext().
+	fn ata_do_ext(&self, dir: Direction, regs: &RegistersWriteExt) -> Result<(RegistersReadExt, Vec<u8>), Error>;
+	// Same for ata_transfer_ext().
+	fn ata_transfer_ext(&self, proto: Protocol, dir: Direction, regs: &RegistersWriteExt, data_out: &[u8], timeout: Option<Duration>) -> Result<(RegistersReadExt, Vec<u8>), Error>;
 
 	/// Issues IDENTIFY DEVICE command, returning a wide range of data, from model name to status of various features.
 	fn get_device_id(&self) -> Result<id::Id, Error> {
@@ -98,9 +146,13 @@ pub trait Misc {
 		Ok(health::parse_smart_status(&regs))
 	}
 
-	/// Issues SMART READ DATA and SMART READ THRESHOLDS commands, then renders their answers using optional [drivedb](../../drivedb/index.html) entry.
-	fn get_smart_attributes(&self, meta: &Option<drivedb::DriveMeta>) -> Result<Vec<attr::SmartAttribute>, Error> {
-		info!("reading SMART attributes and thresholds");
+	/**
+	Issues SMART READ DATA and SMART READ THRESHOLDS commands, then renders their answers using optional [drivedb](../../drivedb/index.html) entry.
+
+	Besides attributes, the result holds off-line data collection and self-test status, SMART capabilities, and whether the checksums of both replies are correct.
+	*/
+	fn get_smart_data(&self, meta: &Option<drivedb::DriveMeta>) -> Result<smart_data::SmartData, Error> {
+		info!("reading SMART data and thresholds");
 
 		let (_, data) = self.ata_do(Direction::From, &RegistersWrite {
 			command: Command::SMART as u8,
@@ -121,18 +173,291 @@ pub trait Misc {
 			device: 0,
 		})?;
 
-		Ok(attr::parse_smart_values(&data, &thresh, &meta))
+		smart_data::parse_smart_data(&data, &thresh, meta)
+			.ok_or(Error::InvalidData("parse SMART READ DATA reply"))
 	}
-}
 
-#[cfg(not(target_os = "linux"))]
-impl Misc for ATADevice<Device> {
-	fn ata_do(&self, dir: Direction, regs: &RegistersWrite) -> Result<(RegistersRead, Vec<u8>), Error> {
-		Ok(Self::ata_do(self, dir, regs)?)
+	/// Same as [`get_smart_data`](#method.get_smart_data), but only returns attributes; checksum mismatches are reported as warnings.
+	fn get_smart_attributes(&self, meta: &Option<drivedb::DriveMeta>) -> Result<Vec<attr::SmartAttribute>, Error> {
+		let data = self.get_smart_data(meta)?;
+		if ! data.checksum_valid {
+			warn!("SMART READ DATA checksum mismatch, attribute values might be corrupted");
+		}
+		if ! data.thresholds_checksum_valid {
+			warn!("SMART READ THRESHOLDS checksum mismatch, attribute thresholds might be corrupted");
+		}
+		Ok(data.attributes)
+	}
+
+	/// Issues SMART EXECUTE OFF-LINE IMMEDIATE with given subcommand; `timeout` overrides device-wide timeout (see [`ATATransport::do_ata_cmd`](../trait.ATATransport.html#tymethod.do_ata_cmd)), which is what captive mode subcommands need.
+	fn smart_execute_offline_immediate(&self, subcommand: u8, timeout: Option<Duration>) -> Result<(), Error> {
+		info!("issuing SMART EXECUTE OFF-LINE IMMEDIATE: subcommand={:#04x}", subcommand);
+
+		let (regs, _) = self.ata_transfer(Protocol::PIO, Direction::None, &RegistersWrite {
+			command: Command::SMART as u8,
+			features: SMARTFeature::ExecuteOfflineImmediate as u8,
+			sector_count: 0,
+			sector: subcommand,
+			cyl_low: 0x4f,
+			cyl_high: 0xc2,
+			device: 0,
+		}, &[], timeout)?;
+		check_status(&regs)
+	}
+
+	/**
+	Starts SMART self-test.
+
+	In off-line mode (`captive` is false), the test runs in the background, and the device keeps serving other commands; use [`get_self_test_progress`](#method.get_self_test_progress) to see how it is going.
+
+	In captive mode, the command does not complete until the test is over, so `timeout` should be large enough to cover the whole test (see polling times in [`get_self_test_progress`](#method.get_self_test_progress)); `None` means device-wide timeout, which is only enough for off-line mode.
+	*/
+	fn start_self_test(&self, test: SelfTest, captive: bool, timeout: Option<Duration>) -> Result<(), Error> {
+		self.smart_execute_offline_immediate(test as u8 | if captive { 0x80 } else { 0 }, timeout)
+	}
+
+	/// Sets `(first, last)` LBA spans (up to five of those) for the selective self-test, then starts it (see [`start_self_test`](#method.start_self_test) on `captive` and `timeout`).
+	fn start_selective_self_test(&self, spans: &[(u64, u64)], captive: bool, timeout: Option<Duration>) -> Result<(), Error> {
+		let log = self_test::selective_self_test_log(spans)
+			.ok_or(Error::InvalidArgument("too many spans for the selective self-test"))?;
+
+		info!("writing selective self-test log: spans={:?}", spans);
+		let (regs, _) = self.ata_transfer(Protocol::PIO, Direction::To, &RegistersWrite {
+			command: Command::SMART as u8,
+			features: SMARTFeature::WriteLog as u8,
+			sector_count: 1,
+			sector: 0x09, // log address
+			cyl_low: 0x4f,
+			cyl_high: 0xc2,
+			device: 0,
+		}, &log, None)?;
+		check_status(&regs)?;
+
+		self.start_self_test(SelfTest::Selective, captive, timeout)
+	}
+
+	/// Aborts off-line mode self-test routine that is currently in progress.
+	fn abort_self_test(&self) -> Result<(), Error> {
+		self.smart_execute_offline_immediate(0x7f, None)
+	}
+
+	/// Issues SMART READ DATA command, returning status of the current (or last) self-test, along with recommended polling times.
+	fn get_self_test_progress(&self) -> Result<self_test::SelfTestProgress, Error> {
+		info!("reading self-test execution status");
+
+		let (_, data) = self.ata_do(Direction::From, &RegistersWrite {
+			command: Command::SMART as u8,
+			sector: 0,
+			features: SMARTFeature::ReadValues as u8,
+			sector_count: 1,
+			cyl_low: 0x4f,
+			cyl_high: 0xc2,
+			device: 0,
+		})?;
+
+		self_test::parse_self_test_progress(&data)
+			.ok_or(Error::InvalidData("parse SMART READ DATA reply"))
+	}
+
+	/// Issues SMART READ LOG command, returning `pages` 512-byte pages of the log at address `log`.
+	fn smart_read_log(&self, log: u8, pages: u8) -> Result<Vec<u8>, Error> {
+		info!("issuing SMART READ LOG: log={:#04x} pages={}", log, pages);
+
+		let (regs, data) = self.ata_do(Direction::From, &RegistersWrite {
+			command: Command::SMART as u8,
+			features: SMARTFeature::ReadLog as u8,
+			sector_count: pages,
+			sector: log,
+			cyl_low: 0x4f,
+			cyl_high: 0xc2,
+			device: 0,
+		})?;
+		check_status(&regs)?;
+		Ok(data)
+	}
+
+	/// Issues READ LOG EXT command, returning `pages` 512-byte pages of the General Purpose Log at address `log`, starting with page `page`.
+	fn read_log_ext(&self, log: u8, page: u16, pages: u16) -> Result<Vec<u8>, Error> {
+		info!("issuing READ LOG EXT: log={:#04x} page={} pages={}", log, page, pages);
+
+		let (regs, data) = self.ata_do_ext(Direction::From, &read_log_regs(Command::ReadLogExt, 0, log, page, pages))?;
+		check_status_bits(regs.status, regs.error)?;
+		Ok(data)
+	}
+
+	/// Same as [`read_log_ext`](#method.read_log_ext), but issues READ LOG DMA EXT instead. Devices that support it have `commands_supported.read_write_dma_ext_gpl` set in their [`Id`](../data/id/struct.Id.html).
+	fn read_log_dma_ext(&self, log: u8, page: u16, pages: u16) -> Result<Vec<u8>, Error> {
+		info!("issuing READ LOG DMA EXT: log={:#04x} page={} pages={}", log, page, pages);
+
+		let (regs, data) = self.ata_transfer_ext(Protocol::DMA, Direction::From, &read_log_regs(Command::ReadLogDMAExt, 0, log, page, pages), &[], None)?;
+		check_status_bits(regs.status, regs.error)?;
+		Ok(data)
+	}
+
+	/**
+	Reads log directory (log address 00h), which lists the logs the device implements, along with their sizes.
+
+	`Source::GPL` requires General Purpose Logging feature set (see `gp_logging_supported` in [`Id`](../data/id/struct.Id.html)), `Source::SMART` requires SMART to be enabled.
+	*/
+	fn get_log_directory(&self, source: log_dir::Source) -> Result<log_dir::LogDirectory, Error> {
+		let data = match source {
+			log_dir::Source::GPL => self.read_log_ext(0x00, 0, 1)?,
+			log_dir::Source::SMART => self.smart_read_log(0x00, 1)?,
+		};
+		log_dir::parse_log_directory(source, &data)
+			.ok_or(Error::InvalidData("parse log directory"))
+	}
+
+	/**
+	Reads `pages` pages of the log at address `log`, starting with page `page`, using the commands of the directory's source.
+
+	Unlike [`read_log_ext`](#method.read_log_ext) or [`smart_read_log`](#method.smart_read_log), this one refuses to read logs that are not listed in the directory `dir`, or to read past the end of the log. Note that SMART READ LOG cannot start at arbitrary page, so `page` must be 0 for SMART logs.
+	*/
+	fn read_log(&self, dir: &log_dir::LogDirectory, log: u8, page: u16, pages: u16) -> Result<Vec<u8>, Error> {
+		let size = dir.pages(log)
+			.ok_or(Error::InvalidArgument("log is not listed in the log directory"))?;
+		if pages == 0 || page as u32 + pages as u32 > size as u32 {
+			return Err(Error::InvalidArgument("pages are out of the log bounds"));
+		}
+
+		match dir.source {
+			log_dir::Source::GPL => self.read_log_ext(log, page, pages),
+			log_dir::Source::SMART => {
+				if page != 0 {
+					return Err(Error::InvalidArgument("SMART logs can only be read from the first page"));
+				}
+				// size is never greater than 255 for SMART logs
+				self.smart_read_log(log, pages as u8)
+			},
+		}
+	}
+
+	/// Reads SMART self-test log (log address 06h), returning up to 21 entries, newest first.
+	fn get_self_test_log(&self) -> Result<Vec<self_test::SelfTestLogEntry>, Error> {
+		let data = self.smart_read_log(0x06, 1)?;
+		self_test::parse_self_test_log(&data)
+			.ok_or(Error::InvalidData("parse SMART self-test log"))
+	}
+
+	/// Reads first `pages` pages of the extended SMART self-test log (GPL log address 07h), returning entries newest first. Number of pages is reported in the GPL directory.
+	fn get_ext_self_test_log(&self, pages: u16) -> Result<Vec<self_test::SelfTestLogEntry>, Error> {
+		let data = self.read_log_ext(0x07, 0, pages)?;
+		self_test::parse_ext_self_test_log(&data)
+			.ok_or(Error::InvalidData("parse extended SMART self-test log"))
+	}
+
+	/// Reads summary SMART error log (log address 01h), which holds up to five most recent errors.
+	fn get_error_log(&self) -> Result<error_log::ErrorLog, Error> {
+		let data = self.smart_read_log(0x01, 1)?;
+		error_log::parse_error_log(&data)
+			.ok_or(Error::InvalidData("parse summary SMART error log"))
+	}
+
+	/// Reads first `pages` pages of the comprehensive SMART error log (log address 02h). Number of pages is reported in the SMART log directory.
+	fn get_comprehensive_error_log(&self, pages: u8) -> Result<error_log::ErrorLog, Error> {
+		let data = self.smart_read_log(0x02, pages)?;
+		error_log::parse_error_log(&data)
+			.ok_or(Error::InvalidData("parse comprehensive SMART error log"))
+	}
+
+	/// Reads first `pages` pages of the extended comprehensive SMART error log (GPL log address 03h). Number of pages is reported in the GPL directory.
+	fn get_ext_error_log(&self, pages: u16) -> Result<error_log::ErrorLog, Error> {
+		let data = self.read_log_ext(0x03, 0, pages)?;
+		error_log::parse_ext_error_log(&data)
+			.ok_or(Error::InvalidData("parse extended comprehensive SMART error log"))
+	}
+
+	/// Reads the whole Device Statistics log (log address 04h), which is found using log directory `dir`, returning statistics that are supported by the device.
+	fn get_device_statistics(&self, dir: &log_dir::LogDirectory) -> Result<Vec<device_statistics::DeviceStatistic>, Error> {
+		let pages = dir.pages(0x04)
+			.ok_or(Error::InvalidArgument("device does not implement Device Statistics log"))?;
+		let data = self.read_log(dir, 0x04, 0, pages)?;
+		device_statistics::parse_device_statistics(&data)
+			.ok_or(Error::InvalidData("parse Device Statistics log"))
+	}
+
+	/**
+	Reads SATA Phy Event Counters log (GPL log address 11h).
+
+	If `reset` is true, the device resets all the counters right after reporting their values.
+	*/
+	fn get_phy_event_counters(&self, reset: bool) -> Result<Vec<phy_events::PhyEventCounter>, Error> {
+		info!("reading SATA Phy Event Counters log: reset={}", reset);
+
+		// features bit 0 is for resetting the counters
+		let (regs, data) = self.ata_do_ext(Direction::From, &read_log_regs(Command::ReadLogExt, reset as u16, 0x11, 0, 1))?;
+		check_status_bits(regs.status, regs.error)?;
+		phy_events::parse_phy_event_counters(&data)
+			.ok_or(Error::InvalidData("parse SATA Phy Event Counters log"))
+	}
+
+	/// Issues SCT command by writing its key page (see [`sct::key_page`](../data/sct/fn.key_page.html)) to log address E0h, returning registers the device replied with.
+	fn sct_command(&self, key: &[u8]) -> Result<RegistersRead, Error> {
+		info!("issuing SCT command: action={:#04x} function={:#04x}", key[0], key[2]);
+
+		let (regs, _) = self.ata_transfer(Protocol::PIO, Direction::To, &RegistersWrite {
+			command: Command::SMART as u8,
+			features: SMARTFeature::WriteLog as u8,
+			sector_count: 1,
+			sector: 0xe0, // log address
+			cyl_low: 0x4f,
+			cyl_high: 0xc2,
+			device: 0,
+		}, key, None)?;
+		check_status(&regs)?;
+		Ok(regs)
+	}
+
+	/// Reads SCT status (log address E0h), which, among other things, holds current and lifetime temperatures.
+	fn get_sct_status(&self) -> Result<sct::SCTStatus, Error> {
+		let data = self.smart_read_log(0xe0, 1)?;
+		sct::parse_sct_status(&data)
+			.ok_or(Error::InvalidData("parse SCT status"))
+	}
+
+	/// Reads HDA temperature history with SCT Data Tables command. Devices that support it have `sct.data_tables` set in their [`Id`](../data/id/struct.Id.html).
+	fn get_sct_temperature_history(&self) -> Result<sct::TemperatureHistory, Error> {
+		// function code 1: read table
+		self.sct_command(&sct::key_page(sct::Action::DataTables, 1, &[sct::TEMPERATURE_HISTORY_TABLE]))?;
+		let data = self.smart_read_log(0xe1, 1)?;
+		sct::parse_temperature_history(&data)
+			.ok_or(Error::InvalidData("parse SCT temperature history"))
+	}
+
+	/**
+	Returns current value of the SCT Error Recovery Control timer, in units of 100 ms; 0 means the timer is disabled, and the device may spend as much time on error recovery as it wants.
+
+	Devices that support it have `sct.error_recovery_control` set in their [`Id`](../data/id/struct.Id.html).
+	*/
+	fn get_sct_erc(&self, timer: sct::ERCTimer) -> Result<u16, Error> {
+		// function code 2: return current value
+		let regs = self.sct_command(&sct::key_page(sct::Action::ErrorRecoveryControl, 2, &[timer as u16]))?;
+		Ok(regs.sector_count as u16 | (regs.sector as u16) << 8)
+	}
+
+	/**
+	Sets SCT Error Recovery Control timer to `value`, in units of 100 ms (e.g. 70 is 7 seconds); 0 disables the timer.
+
+	The value is volatile, i.e. it is lost once the device is power-cycled (or sometimes even reset), so it should be set every time the device shows up.
+	*/
+	fn set_sct_erc(&self, timer: sct::ERCTimer, value: u16) -> Result<(), Error> {
+		// function code 1: set new value
+		self.sct_command(&sct::key_page(sct::Action::ErrorRecoveryControl, 1, &[timer as u16, value]))?;
+		Ok(())
 	}
 }
-impl Misc for ATADevice<SCSIDevice> {
+
+impl<T: ATATransport> Misc for ATADevice<T> {
 	fn ata_do(&self, dir: Direction, regs: &RegistersWrite) -> Result<(RegistersRead, Vec<u8>), Error> {
 		Ok(Self::ata_do(self, dir, regs)?)
 	}
+	fn ata_transfer(&self, proto: Protocol, dir: Direction, regs: &RegistersWrite, data_out: &[u8], timeout: Option<Duration>) -> Result<(RegistersRead, Vec<u8>), Error> {
+		Ok(Self::ata_transfer(self, proto, dir, regs, data_out, timeout)?)
+	}
+	fn ata_do_ext(&self, dir: Direction, regs: &RegistersWriteExt) -> Result<(RegistersReadExt, Vec<u8>), Error> {
+		Ok(Self::ata_do_ext(self, dir, regs)?)
+	}
+	fn ata_transfer_ext(&self, proto: Protocol, dir: Direction, regs: &RegistersWriteExt, data_out: &[u8], timeout: Option<Duration>) -> Result<(RegistersReadExt, Vec<u8>), Error> {
+		Ok(Self::ata_transfer_ext(self, proto, dir, regs, data_out, timeout)?)
+	}
 }
@@ -10,22 +10,41 @@ pub mod data;
 pub mod misc;
 
 use Direction;
-use scsi::{self, SCSIDevice, SCSICommon};
+use scsi::{self, SCSIDevice, SCSICommon, ATAPassThrough};
+
+use std::time::Duration;
 
 #[derive(Debug, Clone, Copy)]
 pub enum Command {
 	Identify = 0xec,
+	ReadLogExt = 0x2f,
+	ReadLogDMAExt = 0x47,
 	SMART = 0xb0,
 }
 #[derive(Debug, Clone, Copy)]
 pub enum SMARTFeature {
 	ReadValues = 0xd0, // in ATA8-ACS it's called 'SMART READ DATA', which is a bit unclear to people not familiar with ATA… or sometimes even to some who knows ATA well
 	ReadThresholds = 0xd1,
+	ExecuteOfflineImmediate = 0xd4,
+	ReadLog = 0xd5,
+	WriteLog = 0xd6,
 	ReturnStatus = 0xda,
 }
 
+/// Data transfer protocol of the command; irrelevant for commands that transfer no data
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serializable", derive(Serialize, Deserialize))]
+pub enum Protocol {
+	PIO,
+	/// Multiword DMA or Ultra DMA, as the transport sees fit (e.g. READ DMA, READ LOG DMA EXT)
+	DMA,
+	/// Ultra DMA specifically
+	UDMA,
+}
+
 // data port is omitted for obvious reasons
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serializable", derive(Serialize, Deserialize))]
 pub struct RegistersRead {
 	pub error: u8,
 
@@ -38,7 +57,8 @@ pub struct RegistersRead {
 
 	pub status: u8,
 }
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serializable", derive(Serialize, Deserialize))]
 pub struct RegistersWrite {
 	pub features: u8,
 
@@ -52,6 +72,111 @@ pub struct RegistersWrite {
 	pub command: u8,
 }
 
+/**
+Registers for 48-bit commands (e.g. READ LOG EXT), as read from the device.
+
+Previous (HOB) and current contents of each register are merged into a single value, so, for example, `lba` is 48-bit LBA and not just lba low/mid/high.
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serializable", derive(Serialize, Deserialize))]
+pub struct RegistersReadExt {
+	pub error: u8,
+
+	pub sector_count: u16,
+
+	pub lba: u64, // 48 bits
+	pub device: u8,
+
+	pub status: u8,
+}
+/// Registers for 48-bit commands (e.g. READ LOG EXT), as written to the device.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serializable", derive(Serialize, Deserialize))]
+pub struct RegistersWriteExt {
+	pub features: u16,
+
+	pub sector_count: u16,
+
+	pub lba: u64, // 48 bits
+	pub device: u8,
+
+	pub command: u8,
+}
+
+// these are used to share code paths between 28-bit and 48-bit commands
+
+impl From<&RegistersWrite> for RegistersWriteExt {
+	fn from(regs: &RegistersWrite) -> Self {
+		Self {
+			features: regs.features as u16,
+			sector_count: regs.sector_count as u16,
+			lba: regs.sector as u64 | (regs.cyl_low as u64) << 8 | (regs.cyl_high as u64) << 16,
+			device: regs.device,
+			command: regs.command,
+		}
+	}
+}
+
+impl From<RegistersReadExt> for RegistersRead {
+	/// Drops previous (HOB) contents of the registers.
+	fn from(regs: RegistersReadExt) -> Self {
+		Self {
+			error: regs.error,
+			sector_count: regs.sector_count as u8,
+			sector: regs.lba as u8,
+			cyl_low: (regs.lba >> 8) as u8,
+			cyl_high: (regs.lba >> 16) as u8,
+			device: regs.device,
+			status: regs.status,
+		}
+	}
+}
+
+/**
+Anything that is capable of executing ATA commands.
+
+This crate implements it for [`SCSIDevice`](../scsi/struct.SCSIDevice.html) (using ATA PASS-THROUGH), for [`Device`](../device/struct.Device.html) on FreeBSD (using CAM) and for [`MockDevice`](../mock/struct.MockDevice.html).
+You probably want to wrap it with [`ATADevice`](struct.ATADevice.html) to get logging and porcelain methods.
+*/
+/**
+Returns the number of bytes that ATA command sent with `sector_count` in direction `dir` transfers (i.e. 0 for `Direction::None`), making sure the request makes sense.
+
+For data transfers, the sector count should not be 0: depending on the command, this means 256 (or 65536) blocks or no data at all, and there's no way to tell which one is it without knowing the command. For `Direction::To`, `data_out` should match the sector count, otherwise the transfer would underrun or overrun.
+*/
+pub fn transfer_len(dir: Direction, sector_count: usize, data_out: &[u8]) -> Result<usize, scsi::ATAError> {
+	let len = 512 * sector_count;
+	match dir {
+		Direction::None => Ok(0),
+		Direction::From | Direction::To if sector_count == 0 => {
+			warn!("refusing to transfer data with zero sector count");
+			Err(scsi::ATAError::InvalidTransfer)
+		},
+		Direction::From => Ok(len),
+		Direction::To if data_out.len() != len => {
+			warn!("refusing to send {} bytes of data with sector count of {} ({} bytes)", data_out.len(), sector_count, len);
+			Err(scsi::ATAError::InvalidTransfer)
+		},
+		Direction::To => Ok(len),
+		Direction::Both => {
+			warn!("refusing to transfer data in both directions, there's no such thing in ATA");
+			Err(scsi::ATAError::InvalidTransfer)
+		},
+	}
+}
+
+pub trait ATATransport {
+	/**
+	Sends `regs` to the device, returning registers and data that the device replied with.
+
+	Amount of data to transfer is derived from the request: it is the number of 512-byte blocks in the sector count register, and for `Direction::To`, `data_out` should be exactly that long. Requests that break these rules (as well as `Direction::Both`, which is not a thing in ATA) fail with `ATAError::InvalidTransfer`; see [`transfer_len`](fn.transfer_len.html).
+
+	`timeout` overrides device-wide timeout for this command only (see [`SCSIDevice::do_cmd`](../scsi/struct.SCSIDevice.html#method.do_cmd)); devices that have no notion of timeouts ignore it.
+	*/
+	fn do_ata_cmd(&self, proto: Protocol, dir: Direction, regs: &RegistersWrite, data_out: &[u8], timeout: Option<Duration>) -> Result<(RegistersRead, Vec<u8>), scsi::ATAError>;
+	/// Same as `do_ata_cmd`, but for 48-bit commands.
+	fn do_ata_cmd_ext(&self, proto: Protocol, dir: Direction, regs: &RegistersWriteExt, data_out: &[u8], timeout: Option<Duration>) -> Result<(RegistersReadExt, Vec<u8>), scsi::ATAError>;
+}
+
 #[derive(Debug)]
 pub struct ATADevice<T> {
 	device: T,
@@ -61,17 +186,33 @@ impl<T> ATADevice<T> {
 	pub fn new(device: T) -> Self {
 		Self { device }
 	}
+
+	/// Return the wrapped device. Useful in cases when ATA PASS-THROUGH is used to determine whether this is an ATA device or not.
+	pub fn unwrap(self) -> T {
+		self.device
+	}
 }
 
-// using macro here instead of trait because we don't want to define yet another trait just to declare that `T` in `ATADevice<T>` is capable of `ata_platform_do()`,
-// which is an implementation detail that would leak everywhere as part of a public interface
-// besides, we really only need this method for just, like, two types: `ATADevice<Device>` and `ATADevice<SCSIDevice>`
-macro_rules! ata_do { ($Err:ty) => {
-	pub fn ata_do(&self, dir: Direction, regs: &::ata::RegistersWrite) -> Result<(::ata::RegistersRead, Vec<u8>), $Err> {
-		info!("issuing cmd: dir={:?} regs={:?}", dir, regs);
+impl<T: ATATransport> ATADevice<T> {
+	/// Shortcut for `ata_transfer` that uses PIO and sends no data to the device, which is what most of the commands need.
+	pub fn ata_do(&self, dir: Direction, regs: &RegistersWrite) -> Result<(RegistersRead, Vec<u8>), scsi::ATAError> {
+		self.ata_transfer(Protocol::PIO, dir, regs, &[], None)
+	}
+
+	/// Same as `ata_do`, but for 48-bit commands.
+	pub fn ata_do_ext(&self, dir: Direction, regs: &RegistersWriteExt) -> Result<(RegistersReadExt, Vec<u8>), scsi::ATAError> {
+		self.ata_transfer_ext(Protocol::PIO, dir, regs, &[], None)
+	}
+
+	// thin wrapper against transport-specific implementation, mainly exists to provide consistent logging between transports
+	/// See [`ATATransport::do_ata_cmd`](trait.ATATransport.html#tymethod.do_ata_cmd).
+	pub fn ata_transfer(&self, proto: Protocol, dir: Direction, regs: &RegistersWrite, data_out: &[u8], timeout: Option<Duration>) -> Result<(RegistersRead, Vec<u8>), scsi::ATAError> {
+		info!("issuing cmd: proto={:?} dir={:?} regs={:?} timeout={:?}", proto, dir, regs, timeout);
+		if ! data_out.is_empty() {
+			debug!("cmd data out: {}", ::utils::hexdump_8(data_out));
+		}
 
-		// this one is implemented in `mod {linux,freebsd}`, and here for `T: SCSIDevice`
-		let ret = Self::ata_platform_do(self, dir, regs);
+		let ret = self.device.do_ata_cmd(proto, dir, regs, data_out, timeout);
 		match &ret {
 			Ok((regs, data)) => {
 				debug!("cmd reply: regs={:?}", regs);
@@ -84,7 +225,27 @@ macro_rules! ata_do { ($Err:ty) => {
 		}
 		ret
 	}
-} }
+
+	/// Same as `ata_transfer`, but for 48-bit commands.
+	pub fn ata_transfer_ext(&self, proto: Protocol, dir: Direction, regs: &RegistersWriteExt, data_out: &[u8], timeout: Option<Duration>) -> Result<(RegistersReadExt, Vec<u8>), scsi::ATAError> {
+		info!("issuing ext cmd: proto={:?} dir={:?} regs={:?} timeout={:?}", proto, dir, regs, timeout);
+		if ! data_out.is_empty() {
+			debug!("cmd data out: {}", ::utils::hexdump_8(data_out));
+		}
+
+		let ret = self.device.do_ata_cmd_ext(proto, dir, regs, data_out, timeout);
+		match &ret {
+			Ok((regs, data)) => {
+				debug!("cmd reply: regs={:?}", regs);
+				debug!("cmd data: {}", ::utils::hexdump_16be(&::utils::bytes_to_be_words(data)));
+			},
+			err => {
+				debug!("cmd error: {:?}", err);
+			},
+		}
+		ret
+	}
+}
 
 /*
 One might notice there's no linux support here. There's a couple of reasons for that:
@@ -99,14 +260,58 @@ mod freebsd;
 #[cfg(target_os = "freebsd")]
 pub use self::freebsd::*;
 
-impl ATADevice<SCSIDevice> {
-	ata_do!(scsi::ATAError);
-	fn ata_platform_do(&self, dir: Direction, regs: &RegistersWrite) -> Result<(RegistersRead, Vec<u8>), scsi::ATAError> {
-		self.device.ata_pass_through_16(dir, regs)
+impl<T: ATATransport + ?Sized> ATATransport for Box<T> {
+	fn do_ata_cmd(&self, proto: Protocol, dir: Direction, regs: &RegistersWrite, data_out: &[u8], timeout: Option<Duration>) -> Result<(RegistersRead, Vec<u8>), scsi::ATAError> {
+		(**self).do_ata_cmd(proto, dir, regs, data_out, timeout)
 	}
+	fn do_ata_cmd_ext(&self, proto: Protocol, dir: Direction, regs: &RegistersWriteExt, data_out: &[u8], timeout: Option<Duration>) -> Result<(RegistersReadExt, Vec<u8>), scsi::ATAError> {
+		(**self).do_ata_cmd_ext(proto, dir, regs, data_out, timeout)
+	}
+}
 
-	/// Return the wrapped device. Useful in cases when ATA PASS-THROUGH is used to determine whether this is an ATA device or not.
-	pub fn unwrap(self) -> SCSIDevice {
-		self.device
+impl ATATransport for SCSIDevice {
+	fn do_ata_cmd(&self, proto: Protocol, dir: Direction, regs: &RegistersWrite, data_out: &[u8], timeout: Option<Duration>) -> Result<(RegistersRead, Vec<u8>), scsi::ATAError> {
+		match self.ata_pass_through() {
+			Some(ATAPassThrough::CDB16) => return self.ata_pass_through_16(proto, dir, regs, data_out, timeout),
+			Some(ATAPassThrough::CDB12) => return self.ata_pass_through_12(proto, dir, regs, data_out, timeout),
+			None => (),
+		}
+
+		match self.ata_pass_through_16(proto, dir, regs, data_out, timeout) {
+			Ok(ret) => {
+				self.set_ata_pass_through(ATAPassThrough::CDB16);
+				Ok(ret)
+			},
+			Err(scsi::ATAError::NotSupported) => {
+				// A1h is BLANK for MMC devices, let's not wipe someone's CD-RW;
+				// if peripheral device type cannot be confirmed, do not risk it either
+				let peripheral_type = self.scsi_inquiry(false, 0).ok()
+					.and_then(|(_, data)| data.first().map(|x| x & 0x1f));
+				match peripheral_type {
+					Some(0x05) | None => {
+						info!("ATA PASS-THROUGH (16) is not supported, and device is either MMC or of unknown type, not falling back to ATA PASS-THROUGH (12)");
+						return Err(scsi::ATAError::NotSupported);
+					},
+					Some(_) => (),
+				}
+
+				info!("ATA PASS-THROUGH (16) is not supported, falling back to ATA PASS-THROUGH (12)");
+				let ret = self.ata_pass_through_12(proto, dir, regs, data_out, timeout)?;
+				self.set_ata_pass_through(ATAPassThrough::CDB12);
+				Ok(ret)
+			},
+			Err(err) => Err(err),
+		}
+	}
+
+	fn do_ata_cmd_ext(&self, proto: Protocol, dir: Direction, regs: &RegistersWriteExt, data_out: &[u8], timeout: Option<Duration>) -> Result<(RegistersReadExt, Vec<u8>), scsi::ATAError> {
+		// there's just no room for previous register contents in ATA PASS-THROUGH (12)
+		if self.ata_pass_through() == Some(ATAPassThrough::CDB12) {
+			return Err(scsi::ATAError::NotSupported);
+		}
+
+		let ret = self.ata_pass_through_16_ext(proto, dir, regs, data_out, timeout)?;
+		self.set_ata_pass_through(ATAPassThrough::CDB16);
+		Ok(ret)
 	}
 }
@@ -16,8 +16,11 @@
 extern crate hdd;
 
 use hdd::{device, Device};
-use hdd::scsi::SCSIDevice;
-use hdd::ata::ATADevice;
+use hdd::scsi::{SCSIDevice, SCSICommon};
+use hdd::ata::{ATADevice, ATATransport};
+use hdd::capture::{Recorder, Capture};
+use hdd::nvme::{NVMeDevice, NVMeCommon};
+use hdd::nvme::data::id as nvme_id;
 
 use hdd::ata::data::id;
 use hdd::drivedb;
@@ -38,12 +41,16 @@ extern crate separator;
 extern crate number_prefix;
 extern crate prettytable;
 
+#[macro_use]
 extern crate log;
 extern crate env_logger;
 use log::LevelFilter;
 use env_logger::Builder as LogBuilder;
 
-use std::path::Path;
+use std::path::PathBuf;
+use std::time::Duration;
+use std::fmt::Debug;
+use std::fs::File;
 
 #[macro_use]
 extern crate lazy_static;
@@ -127,20 +134,37 @@ pub fn open_drivedb(options: Option<Values>) -> Option<drivedb::DriveDB> {
 
 #[cfg(target_os = "linux")]
 arg_enum! {
-	enum Type { Auto, SAT, SCSI }
+	enum Type { Auto, SAT, SCSI, NVMe }
 }
 
 #[cfg(target_os = "freebsd")]
 arg_enum! {
-	enum Type { Auto, ATA, SAT, SCSI }
+	enum Type { Auto, ATA, SAT, SCSI, NVMe }
 }
 
+/// Anything that accepts SCSI commands, and probably ATA commands as well (e.g. `SCSIDevice` through SAT, or replayed captures).
+pub trait Transport: SCSICommon + ATATransport + Debug {}
+impl<T: SCSICommon + ATATransport + Debug> Transport for T {}
+
+/// Anything that only accepts ATA commands (e.g. `Device` on FreeBSD).
+#[cfg(not(target_os = "linux"))]
+pub trait ATAOnlyTransport: ATATransport + Debug {}
+#[cfg(not(target_os = "linux"))]
+impl<T: ATATransport + Debug> ATAOnlyTransport for T {}
+
 #[derive(Debug)]
 pub enum DeviceArgument {
 	#[cfg(not(target_os = "linux"))]
-	ATA(ATADevice<Device>, id::Id),
-	SAT(ATADevice<SCSIDevice>, id::Id),
-	SCSI(SCSIDevice),
+	ATA(ATADevice<Box<ATAOnlyTransport>>, id::Id),
+	SAT(ATADevice<Box<Transport>>, id::Id),
+	SCSI(Box<Transport>),
+	NVMe(NVMeDevice, nvme_id::Controller),
+}
+
+/// Device as it was opened, before we figure out which commands does it support.
+enum Opened {
+	Device(Device),
+	Replay(Capture),
 }
 
 fn main() {
@@ -181,6 +205,32 @@ fn main() {
 			.multiple(true)
 			.help("Verbose output: set once to log actions, twice to also show raw data buffers\ncan also be set though env_logger's RUST_LOG env")
 		)
+		.arg(Arg::with_name("timeout")
+			.long("timeout")
+			.takes_value(true)
+			.value_name("SECONDS")
+			// zero means no timeout for CAM, and for SG_IO it is silently replaced with the default one
+			.validator(|s| match s.parse::<u64>() {
+				Ok(0) => Err("timeout must be at least 1 second".to_string()),
+				Ok(_) => Ok(()),
+				Err(e) => Err(e.to_string()),
+			})
+			.help("Time limit for every command sent to the device")
+		)
+		.arg(Arg::with_name("record")
+			.long("record")
+			.takes_value(true)
+			.value_name("FILE")
+			.conflicts_with("replay")
+			.help("Save every command sent to the device, along with the replies, to a capture file")
+		)
+		.arg(Arg::with_name("replay")
+			.long("replay")
+			.takes_value(true)
+			.value_name("FILE")
+			.conflicts_with("device")
+			.help("Instead of querying real device, reply to commands using previously recorded capture file")
+		)
 		/*
 		Unlike other pretty common arguments like `--json`, and unlike in tools like `smartctl`, `device` appears before the subcommand.
 		Sure this is surprising and "counterintuitive" for users, but there are reasons to do so:
@@ -210,8 +260,32 @@ fn main() {
 	});
 	log.init();
 
-	let path = args.value_of("device").map(|path| Path::new(path));
-	let dev = path.map(|p| Device::open(p).unwrap());
+	let timeout = args.value_of("timeout")
+		// unwrap(): validated by clap
+		.map(|t| Duration::from_secs(t.parse().unwrap()));
+
+	let (path, dev) = match args.value_of("replay") {
+		Some(replay) => {
+			// TODO abort gracefully
+			let capture: Capture = serde_json::from_reader(File::open(replay).unwrap()).unwrap();
+			(
+				capture.device.as_ref().map(PathBuf::from),
+				Some(Opened::Replay(capture)),
+			)
+		},
+		None => {
+			let path = args.value_of("device").map(PathBuf::from);
+			let dev = path.as_ref().map(|p| {
+				let mut dev = Device::open(p).unwrap();
+				if let Some(timeout) = timeout {
+					dev.set_timeout(timeout);
+				}
+				Opened::Device(dev)
+			});
+			(path, dev)
+		},
+	};
+	let path = path.as_deref();
 
 	let dtype = args.value_of("type")
 		.unwrap_or("auto")
@@ -222,57 +296,130 @@ fn main() {
 	let subcommand = SUBCOMMANDS.get(subcommand).unwrap();
 	let sargs = sargs.unwrap();
 
+	// this is where recorded commands end up if `--record` is set
+	let mut log = None;
+
 	/*
 	Why do we issue ATA IDENTIFY DEVICE here?
 	- Device id is what every subcommand uses for one reason or the other, but usually to check whether some feature is supported and enabled.
 	- It allows us to distinguish between pure SCSI devices and ATA devices behind SAT by issuing ATA PASS-THROUGH and checking whether this command is supported.
 	*/
 
-	let dev = dev.map(|dev| match dtype {
-		Type::Auto => {
-			match dev.get_type().unwrap() {
-				device::Type::SCSI => {
-					// check whether devices replies to ATA PASS-THROUGH
-					let satdev = ATADevice::new(SCSIDevice::new(dev));
-					match satdev.get_device_id() {
-						// this is really an ATA device
-						Ok(id) =>
-							DeviceArgument::SAT(satdev, id),
-						// nnnnope, plain SCSI
-						Err(misc::Error::SCSI(ATAError::NotSupported)) =>
-							DeviceArgument::SCSI(satdev.unwrap()),
-						// huh? time to contact Houston
-						// TODO? or should we just keep treating devices that return random garbage (Err(ATAError::NoRegisters), weird sense codes &c) as SCSI?
-						/*
-						e => {
-							e.unwrap(); // TODO abort gracefully
-							unreachable!() // we already panicked
-						},
-						*/
-						_ => DeviceArgument::SCSI(satdev.unwrap()),
-					}
-				},
-				#[cfg(not(target_os = "linux"))]
-				device::Type::ATA => {
-					let atadev = ATADevice::new(dev);
-					let id = atadev.get_device_id().unwrap();
-					DeviceArgument::ATA(atadev, id)
-				},
+	let dev = dev.map(|dev| {
+		let dev = match dev {
+			Opened::Device(dev) => dev,
+			// replayed captures do not retain any information about the type of the device, so probing is all we can do
+			Opened::Replay(capture) => return probe(dtype, Box::new(capture.replay())),
+		};
+
+		let dtype = match dtype {
+			Type::Auto => {
+				let (dev_type, reason) = dev.get_type_reason().unwrap();
+				info!("detected device type: {:?} ({})", dev_type, reason);
+				match dev_type {
+					// might still turn out to be SAT
+					device::Type::SCSI => Type::Auto,
+					// sysfs only knows that SCSI vendor is "ATA"; some SATLs (e.g. on SAS HBAs) still might not answer ATA PASS-THROUGH, so probe these as well, and fall back to SCSI if they do not
+					#[cfg(target_os = "linux")]
+					device::Type::SAT => Type::Auto,
+					// whether these support ATA PASS-THROUGH depends on the bridge or the hypervisor, so probe them as well
+					#[cfg(target_os = "linux")]
+					device::Type::USB | device::Type::VirtioSCSI => Type::Auto,
+					#[cfg(target_os = "linux")]
+					device::Type::VirtioBlk => {
+						eprint!("virtio-blk devices accept neither SCSI nor ATA commands\n");
+						::std::process::exit(1);
+					},
+					#[cfg(target_os = "linux")]
+					device::Type::NVMe => Type::NVMe,
+					#[cfg(not(target_os = "linux"))]
+					device::Type::ATA => Type::ATA,
+				}
+			},
+			dtype => dtype,
+		};
+
+		if let Type::NVMe = dtype {
+			// captures only cover SCSI and ATA commands
+			if args.is_present("record") {
+				eprint!("Recording commands is not supported for NVMe devices\n");
+				::std::process::exit(1);
 			}
-		},
-		#[cfg(target_os = "freebsd")]
-		Type::ATA => {
-			let dev = ATADevice::new(dev);
-			let id = dev.get_device_id().unwrap();
-			DeviceArgument::ATA(dev, id)
-		},
+			let dev = NVMeDevice::new(dev);
+			let id = dev.identify_controller().unwrap();
+			return DeviceArgument::NVMe(dev, id);
+		}
+
+		#[cfg(not(target_os = "linux"))]
+		{
+			if let Type::ATA = dtype {
+				let dev: Box<ATAOnlyTransport> = if args.is_present("record") {
+					let dev = Recorder::new(dev);
+					log = Some(dev.log());
+					Box::new(dev)
+				} else {
+					Box::new(dev)
+				};
+				let dev = ATADevice::new(dev);
+				let id = dev.get_device_id().unwrap();
+				return DeviceArgument::ATA(dev, id);
+			}
+		}
+
+		let dev = SCSIDevice::new(dev);
+		let dev: Box<Transport> = if args.is_present("record") {
+			let dev = Recorder::new(dev);
+			log = Some(dev.log());
+			Box::new(dev)
+		} else {
+			Box::new(dev)
+		};
+		probe(dtype, dev)
+	});
+
+	subcommand.run(&path, &dev.as_ref(), sargs);
+
+	if let (Some(file), Some(log)) = (args.value_of("record"), log) {
+		let capture = Capture {
+			device: path.map(|p| p.to_string_lossy().into_owned()),
+			entries: log.borrow().clone(),
+		};
+		// TODO abort gracefully
+		serde_json::to_writer_pretty(&mut File::create(file).unwrap(), &capture).unwrap();
+	}
+}
+
+/// Figures out whether `dev` is a SCSI device or ATA device behind SAT.
+fn probe(dtype: Type, dev: Box<Transport>) -> DeviceArgument {
+	match dtype {
 		Type::SAT => {
-			let dev = ATADevice::new(SCSIDevice::new(dev));
+			let dev = ATADevice::new(dev);
 			let id = dev.get_device_id().unwrap();
 			DeviceArgument::SAT(dev, id)
 		},
-		Type::SCSI => DeviceArgument::SCSI(SCSIDevice::new(dev)),
-	});
-
-	subcommand.run(&path, &dev.as_ref(), sargs)
+		Type::SCSI => DeviceArgument::SCSI(dev),
+		// `Type::ATA` ends up here with replayed captures: there's no way to tell whether capture came from the ATA device or from SAT, and it doesn't matter anyway
+		// (the same goes for `Type::NVMe`, as captures do not cover NVMe commands yet)
+		_ => {
+			// check whether devices replies to ATA PASS-THROUGH
+			let satdev = ATADevice::new(dev);
+			match satdev.get_device_id() {
+				// this is really an ATA device
+				Ok(id) =>
+					DeviceArgument::SAT(satdev, id),
+				// nnnnope, plain SCSI
+				Err(misc::Error::SCSI(ATAError::NotSupported)) =>
+					DeviceArgument::SCSI(satdev.unwrap()),
+				// huh? time to contact Houston
+				// TODO? or should we just keep treating devices that return random garbage (Err(ATAError::NoRegisters), weird sense codes &c) as SCSI?
+				/*
+				e => {
+					e.unwrap(); // TODO abort gracefully
+					unreachable!() // we already panicked
+				},
+				*/
+				_ => DeviceArgument::SCSI(satdev.unwrap()),
+			}
+		},
+	}
 }
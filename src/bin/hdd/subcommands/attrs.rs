@@ -2,6 +2,11 @@ use hdd::ata::misc::Misc;
 
 use hdd::ata::data::attr;
 use hdd::ata::data::attr::raw::Raw;
+use hdd::ata::data::attr::kind::{self, Classification};
+use hdd::ata::data::id;
+use hdd::ata::data::log_dir::{LogDirectory, Source};
+use hdd::ata::data::device_statistics::DeviceStatistic;
+use hdd::ata::data::phy_events::{self, PhyEventCounter};
 use hdd::drivedb;
 use hdd::drivedb::vendor_attribute;
 
@@ -9,6 +14,9 @@ use hdd::scsi::pages::{SCSIPages, ErrorCounter};
 use hdd::scsi::{SCSICommon, DefectList};
 use hdd::scsi::data::inquiry;
 
+use hdd::nvme::NVMeCommon;
+use hdd::nvme::data::smart::SmartLog;
+
 use clap::{
 	Arg,
 	ArgMatches,
@@ -26,6 +34,7 @@ use std::path::Path;
 use std::f64::NAN;
 
 use number_prefix::{decimal_prefix, binary_prefix, Prefixed, Standalone};
+use separator::Separatable;
 
 use prettytable;
 use prettytable::Table;
@@ -39,7 +48,6 @@ fn bool_to_flag(b: bool, c: char) -> char {
 	if b { c } else { '-' }
 }
 
-// XXX only `pretty_attributes` clearly shows failing/failed attributes
 fn print_attributes(values: Vec<attr::SmartAttribute>) {
 	if values.is_empty() {
 		print!("No S.M.A.R.T. attributes found.\n");
@@ -64,13 +72,11 @@ fn print_attributes(values: Vec<attr::SmartAttribute>) {
 			val.value.map(|v| format!("{:3}", v)).unwrap_or("---".to_string()),
 			val.worst.map(|v| format!("{:3}", v)).unwrap_or("---".to_string()),
 			val.thresh.map(|v| format!("{:3}", v)).unwrap_or("(?)".to_string()),
-			match (val.value, val.worst, val.thresh) {
-				(Some(v), _, Some(t)) if v <= t => "NOW ",
-				(_, Some(w), Some(t)) if w <= t => "past",
-				// either value/worst are part of the `val.row`,
-				// or threshold is not available,
-				// or value never was below the threshold
-				_ => "-   ",
+			match val.status {
+				attr::Status::FailingNow => "NOW ",
+				attr::Status::FailedInPast => "past",
+				attr::Status::Unknown => "?   ",
+				attr::Status::Ok => "-   ",
 			},
 			val.raw,
 		);
@@ -121,6 +127,12 @@ fn print_prometheus_values(labels: &HashMap<&str, String>, values: Vec<attr::Sma
 		val.value.map(|v| print!("{}\n", format_prom("smart_value", &labels, v)));
 		val.worst.map(|v| print!("{}\n", format_prom("smart_worst", &labels, v)));
 		val.thresh.map(|v| print!("{}\n", format_prom("smart_thresh", &labels, v)));
+		for status in &[attr::Status::Ok, attr::Status::FailingNow, attr::Status::FailedInPast, attr::Status::Unknown] {
+			let mut labels = labels.clone();
+			labels.insert("status", status.name().to_string());
+			print!("{}\n", format_prom("smart_status", &labels, (val.status == *status) as u8));
+		}
+
 		print!("{}\n", format_prom("smart_raw", &labels, {
 			use self::Raw::*;
 			match val.raw {
@@ -142,6 +154,127 @@ fn print_prometheus_values(labels: &HashMap<&str, String>, values: Vec<attr::Sma
 				CelsiusMinMax { current, .. } => current as f64,
 			}
 		}));
+
+		// unlike smart_raw above, these are converted into their units, which are also exported
+		if let Some(x) = val.raw.primary() {
+			let mut labels = labels.clone();
+			labels.insert("unit", val.raw.unit().name().to_string());
+			print!("{}\n", format_prom("smart_raw_value", &labels, x));
+		}
+		for (component, unit, x) in val.raw.secondary() {
+			let mut labels = labels.clone();
+			labels.insert("component", component);
+			labels.insert("unit", unit.name().to_string());
+			print!("{}\n", format_prom("smart_raw_secondary", &labels, x));
+		}
+	}
+}
+
+fn print_prom_semantic(labels: &HashMap<&str, String>, classified: &[Classification]) {
+	for c in classified {
+		if let Some(value) = c.value {
+			let mut labels = labels.clone();
+			labels.insert("id", c.id.to_string());
+			labels.insert("kind", c.kind.name().to_string());
+			print!("{}\n", format_prom("smart_semantic", &labels, value));
+		}
+	}
+}
+
+// logs are optional, hence all the errors below are only reported as warnings
+
+fn get_log_directory(misc: &Misc, id: &id::Id) -> Option<LogDirectory> {
+	let source = if id.gp_logging_supported { Source::GPL } else { Source::SMART };
+	misc.get_log_directory(source)
+		.map_err(|err| warn!("cannot read log directory: {}", err))
+		.ok()
+}
+
+fn get_device_statistics(misc: &Misc, dir: &LogDirectory) -> Option<Vec<DeviceStatistic>> {
+	if ! dir.contains(0x04) {
+		return None;
+	}
+	misc.get_device_statistics(dir)
+		.map_err(|err| warn!("cannot read device statistics: {}", err))
+		.ok()
+}
+
+fn get_phy_event_counters(misc: &Misc, dir: &LogDirectory, reset: bool) -> Option<Vec<PhyEventCounter>> {
+	// this one is only available with GPL commands
+	if dir.source != Source::GPL || ! dir.contains(0x11) {
+		if reset {
+			eprint!("SATA Phy Event Counters log is not supported, cannot reset counters\n");
+		}
+		return None;
+	}
+	misc.get_phy_event_counters(reset)
+		.map_err(|err| warn!("cannot read SATA Phy Event Counters: {}", err))
+		.ok()
+}
+
+fn phy_event_counter_name(counter: &PhyEventCounter) -> String {
+	use self::phy_events::Counter::*;
+	match (counter.counter.name(), counter.counter) {
+		(Some(name), _) => name.to_string(),
+		(None, VendorSpecific(id)) => format!("vendor-specific-{:#05x}", id),
+		(None, _) => format!("reserved-{:#05x}", counter.id),
+	}
+}
+
+fn print_phy_event_counters(counters: &[PhyEventCounter]) {
+	if counters.is_empty() {
+		print!("No SATA Phy Event Counters found.\n");
+		return;
+	}
+
+	print!("SATA Phy Event Counters:\n");
+	print!("    ID size counter                                   value\n");
+	for counter in counters {
+		print!("{:#06x} {:4} {:.<40} {}{}\n",
+			counter.id,
+			counter.width,
+			phy_event_counter_name(counter),
+			counter.value,
+			if counter.saturated { "+" } else { "" },
+		);
+	}
+}
+
+fn print_prom_phy_event_counters(labels: &HashMap<&str, String>, counters: &[PhyEventCounter]) {
+	for counter in counters {
+		let mut labels = labels.clone();
+		labels.insert("id", counter.id.to_string());
+		labels.insert("name", phy_event_counter_name(counter));
+		print!("{}\n", format_prom("sata_phy_event_counter", &labels, counter.value));
+	}
+}
+
+fn print_device_statistics(stats: &[DeviceStatistic]) {
+	if stats.is_empty() {
+		print!("No device statistics found.\n");
+		return;
+	}
+
+	print!("Device statistics:\n");
+	print!(" page           statistic                                          value\n");
+	for stat in stats {
+		print!(" {:<14} {:.<50} {}{}\n",
+			stat.page.name(),
+			stat.statistic.name(),
+			stat.value.map(|v| v.to_string()).unwrap_or("-".to_string()),
+			if stat.normalized { " (normalized)" } else { "" },
+		);
+	}
+}
+
+fn print_prom_device_statistics(labels: &HashMap<&str, String>, stats: &[DeviceStatistic]) {
+	for stat in stats {
+		if let Some(value) = stat.value {
+			let mut labels = labels.clone();
+			labels.insert("page", stat.page.name().to_string());
+			labels.insert("name", stat.statistic.name().to_string());
+			print!("{}\n", format_prom("ata_device_statistic", &labels, value));
+		}
 	}
 }
 
@@ -166,6 +299,15 @@ impl Subcommand for Attrs {
 				.help("alias for --format=json")
 				.overrides_with("format")
 			)
+			.arg(Arg::with_name("extended")
+				.long("extended")
+				// top-level array is kept by default to not break existing consumers
+				.help("with --format=json, print an object that, along with attributes, holds checksums, device statistics and other data, instead of a list of attributes (ATA devices only)")
+			)
+			.arg(Arg::with_name("reset-phy-event-counters")
+				.long("reset-phy-event-counters")
+				.help("reset SATA Phy Event Counters after reading them (ATA devices only)")
+			)
 			.arg(arg_drivedb())
 			.arg(Arg::with_name("vendorattribute")
 				.multiple(true)
@@ -214,13 +356,16 @@ impl Subcommand for Attrs {
 			.map(|x| x.unwrap())
 			.collect();
 		let drivedb = open_drivedb(args.values_of("drivedb"));
+		let reset_phy_events = args.is_present("reset-phy-event-counters");
+		let extended = args.is_present("extended");
 
 		use DeviceArgument::*;
 		match dev {
 			#[cfg(not(target_os = "linux"))]
-			dev @ ATA(_, _) => attrs_ata(path, dev, format, drivedb, user_attributes),
-			dev @ SAT(_, _) => attrs_ata(path, dev, format, drivedb, user_attributes),
+			dev @ ATA(_, _) => attrs_ata(path, dev, format, drivedb, user_attributes, reset_phy_events, extended),
+			dev @ SAT(_, _) => attrs_ata(path, dev, format, drivedb, user_attributes, reset_phy_events, extended),
 			dev @ SCSI(_) => attrs_scsi(path, dev, format),
+			dev @ NVMe(_, _) => attrs_nvme(path, dev, format),
 		};
 	}
 }
@@ -229,12 +374,13 @@ impl Subcommand for Attrs {
 enum Format { Plain, JSON, Prometheus }
 use self::Format::*;
 
-fn attrs_ata(path: &str, dev: &DeviceArgument, format: Format, drivedb: Option<drivedb::DriveDB>, user_attributes: Vec<drivedb::Attribute>) {
+fn attrs_ata(path: &str, dev: &DeviceArgument, format: Format, drivedb: Option<drivedb::DriveDB>, user_attributes: Vec<drivedb::Attribute>, reset_phy_events: bool, extended: bool) {
 	let id = match dev {
 		#[cfg(not(target_os = "linux"))]
 		DeviceArgument::ATA(_, id) => id,
 		DeviceArgument::SAT(_, id) => id,
 		DeviceArgument::SCSI(_) => unreachable!(),
+		DeviceArgument::NVMe(_, _) => unreachable!(),
 	};
 
 	let dbentry = drivedb.as_ref().map(|drivedb| drivedb.render_meta(
@@ -272,23 +418,76 @@ fn attrs_ata(path: &str, dev: &DeviceArgument, format: Format, drivedb: Option<d
 			print!("{}\n", format_prom("smart_enabled", &labels, 0)),
 
 		(format, Enabled) => {
-			let values = match dev {
+			let misc: &Misc = match dev {
 				#[cfg(not(target_os = "linux"))]
-				DeviceArgument::ATA(dev, _) => dev.get_smart_attributes(&dbentry).unwrap(),
-				DeviceArgument::SAT(dev, _) => dev.get_smart_attributes(&dbentry).unwrap(),
+				DeviceArgument::ATA(dev, _) => dev,
+				DeviceArgument::SAT(dev, _) => dev,
 				DeviceArgument::SCSI(_) => unreachable!(),
+				DeviceArgument::NVMe(_, _) => unreachable!(),
 			};
 
+			let data = misc.get_smart_data(&dbentry).unwrap();
+			let checksums = [
+				("values", data.checksum_valid),
+				("thresholds", data.thresholds_checksum_valid),
+			];
+			let values = data.attributes;
+			let classified = kind::classify_all(&values, id);
+
+			let dir = get_log_directory(misc, id);
+			let stats = dir.as_ref().and_then(|dir| get_device_statistics(misc, dir));
+			let phy_events = dir.as_ref().and_then(|dir| get_phy_event_counters(misc, dir, reset_phy_events));
+
 			match format {
-				Plain => print_attributes(values),
-				JSON => print!("{}\n",
+				Plain => {
+					for &(sector, valid) in &checksums {
+						if ! valid {
+							eprint!("Warning: S.M.A.R.T. {} checksum mismatch, data might be corrupted\n", sector);
+						}
+					}
+					print_attributes(values);
+					if let Some(stats) = stats {
+						print!("\n");
+						print_device_statistics(&stats);
+					}
+					if let Some(counters) = phy_events {
+						print!("\n");
+						print_phy_event_counters(&counters);
+					}
+				},
+				JSON if ! extended => print!("{}\n",
 					serde_json::to_string(
 						&values.to_json().unwrap()
 					).unwrap()
 				),
+				JSON => {
+					let mut json = serde_json::Map::new();
+					json.insert("attributes".to_string(), values.to_json().unwrap());
+					json.insert("semantic".to_string(), classified.to_json().unwrap());
+					let mut checksum_valid = serde_json::Map::new();
+					for &(sector, valid) in &checksums {
+						checksum_valid.insert(sector.to_string(), valid.to_json().unwrap());
+					}
+					json.insert("checksum-valid".to_string(), checksum_valid.to_json().unwrap());
+					json.insert("device-statistics".to_string(), stats.to_json().unwrap());
+					json.insert("phy-event-counters".to_string(), phy_events.to_json().unwrap());
+					print!("{}\n", serde_json::to_string(&json).unwrap());
+				},
 				Prometheus => {
 					print!("{}\n", format_prom("smart_enabled", &labels, 1));
+					for &(sector, valid) in &checksums {
+						let mut labels = labels.clone();
+						labels.insert("sector", sector.to_string());
+						print!("{}\n", format_prom("smart_checksum_valid", &labels, valid as u8));
+					}
 					print_prometheus_values(&labels, values);
+					print_prom_semantic(&labels, &classified);
+					if let Some(stats) = stats {
+						print_prom_device_statistics(&labels, &stats);
+					}
+					if let Some(counters) = phy_events {
+						print_prom_phy_event_counters(&labels, &counters);
+					}
 				},
 			}
 		},
@@ -474,6 +673,7 @@ fn attrs_scsi(path: &str, dev: &DeviceArgument, format: Format) {
 		DeviceArgument::ATA(_, _) => unreachable!(),
 		DeviceArgument::SAT(_, _) => unreachable!(),
 		DeviceArgument::SCSI(dev) => dev,
+		DeviceArgument::NVMe(_, _) => unreachable!(),
 	};
 
 	let mut pages = SCSIPages::new(dev);
@@ -645,3 +845,129 @@ fn attrs_scsi(path: &str, dev: &DeviceArgument, format: Format) {
 		print!("{}\n", serde_json::to_string(&json).unwrap());
 	}
 }
+
+fn kelvin_to_celsius(t: u16) -> i32 {
+	t as i32 - 273
+}
+
+fn print_human_nvme_smart_log(log: &SmartLog) {
+	// data units are thousands of 512-byte blocks
+	fn data_units(units: u64) -> String {
+		format!("{} ({})", units.separated_string(), match decimal_prefix(units as f32 * 512000.) {
+			Prefixed(p, x) => format!("{:.1} {}B", x, p),
+			Standalone(x)  => format!("{} B", x),
+		})
+	}
+
+	let warn = &log.critical_warning;
+
+	print!("SMART / Health Information:\n");
+	print!("Critical warning:           {}\n", if ! warn.any() { "none".to_string() } else {
+		let mut warnings = vec![];
+		if warn.available_spare   { warnings.push("available spare below threshold") }
+		if warn.temperature       { warnings.push("temperature") }
+		if warn.reliability       { warnings.push("reliability degraded") }
+		if warn.read_only         { warnings.push("read-only") }
+		if warn.volatile_backup   { warnings.push("volatile memory backup failed") }
+		if warn.persistent_memory { warnings.push("persistent memory region") }
+		warnings.join(", ")
+	});
+	print!("Temperature:                {}°C\n", kelvin_to_celsius(log.temperature));
+	for (i, t) in log.temperature_sensors.iter().enumerate() {
+		if let Some(t) = t {
+			print!("Temperature sensor {}:       {}°C\n", i + 1, kelvin_to_celsius(*t));
+		}
+	}
+	print!("Available spare:            {}%\n", log.available_spare);
+	print!("Available spare threshold:  {}%\n", log.available_spare_threshold);
+	print!("Percentage used:            {}%\n", log.percentage_used);
+	print!("Data units read:            {}\n", data_units(log.data_units_read));
+	print!("Data units written:         {}\n", data_units(log.data_units_written));
+	print!("Host read commands:         {}\n", log.host_read_commands.separated_string());
+	print!("Host write commands:        {}\n", log.host_write_commands.separated_string());
+	print!("Controller busy time:       {} min\n", log.controller_busy_time.separated_string());
+	print!("Power cycles:               {}\n", log.power_cycles.separated_string());
+	print!("Power-on hours:             {}\n", log.power_on_hours.separated_string());
+	print!("Unsafe shutdowns:           {}\n", log.unsafe_shutdowns.separated_string());
+	print!("Media and integrity errors: {}\n", log.media_errors.separated_string());
+	print!("Error log entries:          {}\n", log.error_log_entries.separated_string());
+	print!("Warning temperature time:   {} min\n", log.warning_temp_time);
+	print!("Critical temperature time:  {} min\n", log.critical_temp_time);
+}
+
+fn print_prom_nvme_smart_log(labels: &HashMap<&str, String>, log: &SmartLog) {
+	{
+		let warn = &log.critical_warning;
+		for &(kind, set) in &[
+			("available-spare", warn.available_spare),
+			("temperature", warn.temperature),
+			("reliability", warn.reliability),
+			("read-only", warn.read_only),
+			("volatile-backup", warn.volatile_backup),
+			("persistent-memory", warn.persistent_memory),
+		] {
+			let mut labels = labels.clone();
+			labels.insert("kind", kind.to_string());
+			print!("{}\n", format_prom("nvme_critical_warning", &labels, set as u8));
+		}
+	}
+
+	print!("{}\n", format_prom("nvme_temperature", labels, kelvin_to_celsius(log.temperature)));
+	for (i, t) in log.temperature_sensors.iter().enumerate() {
+		if let Some(t) = t {
+			let mut labels = labels.clone();
+			labels.insert("sensor", (i + 1).to_string());
+			print!("{}\n", format_prom("nvme_temperature_sensor", &labels, kelvin_to_celsius(*t)));
+		}
+	}
+
+	print!("{}\n", format_prom("nvme_available_spare", labels, log.available_spare));
+	print!("{}\n", format_prom("nvme_available_spare_threshold", labels, log.available_spare_threshold));
+	print!("{}\n", format_prom("nvme_percentage_used", labels, log.percentage_used));
+
+	for &(action, units, commands) in &[
+		("read", log.data_units_read, log.host_read_commands),
+		("write", log.data_units_written, log.host_write_commands),
+	] {
+		let mut labels = labels.clone();
+		labels.insert("action", action.to_string());
+		print!("{}\n", format_prom("nvme_data_units", &labels, units));
+		print!("{}\n", format_prom("nvme_host_commands", &labels, commands));
+	}
+
+	print!("{}\n", format_prom("nvme_controller_busy_minutes", labels, log.controller_busy_time));
+	print!("{}\n", format_prom("nvme_power_cycles", labels, log.power_cycles));
+	print!("{}\n", format_prom("nvme_power_on_hours", labels, log.power_on_hours));
+	print!("{}\n", format_prom("nvme_unsafe_shutdowns", labels, log.unsafe_shutdowns));
+	print!("{}\n", format_prom("nvme_media_errors", labels, log.media_errors));
+	print!("{}\n", format_prom("nvme_error_log_entries", labels, log.error_log_entries));
+	print!("{}\n", format_prom("nvme_warning_temperature_minutes", labels, log.warning_temp_time));
+	print!("{}\n", format_prom("nvme_critical_temperature_minutes", labels, log.critical_temp_time));
+}
+
+fn attrs_nvme(path: &str, dev: &DeviceArgument, format: Format) {
+	let (dev, id) = match dev {
+		#[cfg(not(target_os = "linux"))]
+		DeviceArgument::ATA(_, _) => unreachable!(),
+		DeviceArgument::SAT(_, _) => unreachable!(),
+		DeviceArgument::SCSI(_) => unreachable!(),
+		DeviceArgument::NVMe(dev, id) => (dev, id),
+	};
+
+	let log = dev.smart_log().unwrap_or_else(|err| {
+		eprint!("cannot read SMART / Health Information log: {}\n", err);
+		::std::process::exit(1);
+	});
+
+	let mut labels = HashMap::new();
+	labels.insert("dev", path.to_string());
+	labels.insert("model", id.model.clone());
+	labels.insert("serial", id.serial.clone());
+	labels.insert("firmware", id.firmware.clone());
+
+	match format {
+		Plain => print_human_nvme_smart_log(&log),
+		JSON => print!("{}\n", serde_json::to_string(&log.to_json().unwrap()).unwrap()),
+		Prometheus => print_prom_nvme_smart_log(&labels, &log),
+	}
+}
@@ -0,0 +1,177 @@
+use hdd::ata::misc::Misc;
+use hdd::ata::data::error_log::ErrorLog;
+use hdd::ata::data::id::Id;
+use hdd::ata::data::log_dir::Source;
+
+use clap::{
+	Arg,
+	ArgMatches,
+	App,
+	SubCommand,
+};
+
+use serde_json;
+use serde_json::value::ToJson;
+
+use ::{DeviceArgument, when_smart_enabled};
+use super::{Subcommand, arg_json};
+
+use std::path::Path;
+
+// ATA8-ACS, 6.2 Error register
+fn error_bits(error: u8) -> String {
+	let bits = [
+		(7, "ICRC"),
+		(6, "UNC"),
+		(4, "IDNF"),
+		(2, "ABRT"),
+		(1, "EOM"),
+		(0, "AMNF"),
+	];
+	bits.iter()
+		.filter(|(bit, _)| error & (1<<bit) != 0)
+		.map(|(_, name)| *name)
+		.collect::<Vec<_>>()
+		.join(" ")
+}
+
+// number of pages of the log, as reported in the log directory; the first page is always there, so stick to it if the directory is not available
+fn log_pages(misc: &Misc, source: Source, log: u8) -> u16 {
+	match misc.get_log_directory(source) {
+		Ok(dir) => dir.pages(log).unwrap_or(1),
+		Err(err) => {
+			warn!("cannot read log directory: {}", err);
+			1
+		},
+	}
+}
+
+// extended comprehensive log is the most detailed one, but even devices that support GPL commands do not necessarily keep it, while summary log is always there
+fn default_log(misc: &Misc, id: &Id) -> &'static str {
+	if ! id.gp_logging_supported {
+		return "summary";
+	}
+	match misc.get_log_directory(Source::GPL) {
+		Ok(ref dir) if dir.contains(0x03) => "extended",
+		Ok(_) => "summary",
+		Err(err) => {
+			warn!("cannot read log directory: {}", err);
+			"summary"
+		},
+	}
+}
+
+fn format_timestamp(ms: u32) -> String {
+	let s = ms / 1000;
+	format!("{}d+{:02}:{:02}:{:02}.{:03}", s / 86400, s / 3600 % 24, s / 60 % 60, s % 60, ms % 1000)
+}
+
+fn print_human(name: &str, log: &ErrorLog) {
+	print!("{} (errors total: {})\n", name, log.error_count);
+
+	if log.errors.is_empty() {
+		print!("No errors logged.\n");
+		return;
+	}
+
+	for (i, err) in log.errors.iter().enumerate() {
+		print!("\n");
+		// errors are numbered the way smartctl does it
+		print!("Error {} occurred at {} hours, device state: {:?}\n",
+			(log.error_count as usize).saturating_sub(i),
+			err.power_on_hours,
+			err.state,
+		);
+		print!("  Error: {:#04x} ({}), status: {:#04x}, count: {}, LBA: {} ({:#x}), device: {:#04x}\n",
+			err.error, error_bits(err.error),
+			err.status,
+			err.count,
+			err.lba, err.lba,
+			err.device,
+		);
+
+		if ! err.commands.is_empty() {
+			print!("  Commands leading to the error:\n");
+			print!("    CMD  FEATR COUNT LBA            DEV  DC   Power-up time\n");
+			for cmd in &err.commands {
+				print!("    {:02x}   {:04x}  {:04x}  {:012x}   {:02x}   {:02x}   {}\n",
+					cmd.command,
+					cmd.features,
+					cmd.count,
+					cmd.lba,
+					cmd.device,
+					cmd.device_control,
+					format_timestamp(cmd.timestamp),
+				);
+			}
+		}
+	}
+}
+
+pub struct ErrorLogCmd {}
+impl Subcommand for ErrorLogCmd {
+	fn subcommand(&self) -> App<'static, 'static> {
+		SubCommand::with_name("errorlog")
+			.about("Prints S.M.A.R.T. error log")
+			.arg(Arg::with_name("log")
+				.long("log")
+				.takes_value(true)
+				.possible_values(&["summary", "comprehensive", "extended"])
+				.help("which log to read (default: extended comprehensive log if device has one, summary log otherwise)")
+			)
+			.arg(arg_json())
+	}
+
+	fn run(
+		&self,
+		_: &Option<&Path>,
+		dev: &Option<&DeviceArgument>,
+		args: &ArgMatches,
+	) {
+		let dev = dev.unwrap_or_else(|| {
+			// TODO show usage and whatnot
+			eprint!("<device> is required\n");
+			::std::process::exit(1);
+		});
+
+		let (misc, id): (&Misc, _) = match dev {
+			#[cfg(not(target_os = "linux"))]
+			DeviceArgument::ATA(dev, id) => (dev, id),
+			DeviceArgument::SAT(dev, id) => (dev, id),
+			DeviceArgument::SCSI(_) | DeviceArgument::NVMe(_, _) => {
+				eprint!("S.M.A.R.T. error log is only available for ATA devices\n");
+				::std::process::exit(1);
+			},
+		};
+
+		let use_json = args.is_present("json");
+		let log = args.value_of("log");
+
+		when_smart_enabled(&id.smart, "error log", || {
+			let log = log.unwrap_or_else(|| default_log(misc, id));
+			let (name, errors) = match log {
+				"summary" => ("Summary SMART error log", misc.get_error_log()),
+				"comprehensive" => {
+					let pages = log_pages(misc, Source::SMART, 0x02);
+					("Comprehensive SMART error log", misc.get_comprehensive_error_log(pages as u8))
+				},
+				"extended" => {
+					let pages = log_pages(misc, Source::GPL, 0x03);
+					("Extended comprehensive SMART error log", misc.get_ext_error_log(pages))
+				},
+				_ => unreachable!(),
+			};
+
+			let errors = errors.unwrap_or_else(|err| {
+				eprint!("Cannot read error log: {}\n", err);
+				::std::process::exit(1);
+			});
+
+			if use_json {
+				print!("{}\n", serde_json::to_string(&errors.to_json().unwrap()).unwrap());
+			} else {
+				print_human(name, &errors);
+			}
+		});
+	}
+}
@@ -1,4 +1,5 @@
 use hdd::ata::misc::Misc;
+use hdd::nvme::NVMeCommon;
 
 use clap::{
 	App,
@@ -34,32 +35,48 @@ impl Subcommand for Health {
 			::std::process::exit(1);
 		});
 
+		let use_json = args.is_present("json");
+
+		// NVMe devices have no notion of SMART being disabled
+		if let DeviceArgument::NVMe(dev, _) = dev {
+			let log = dev.smart_log().unwrap_or_else(|err| {
+				eprint!("Cannot read SMART / Health Information log: {}\n", err);
+				::std::process::exit(1);
+			});
+			print_health(Some(! log.critical_warning.any()), use_json);
+			return;
+		}
+
 		let id = match dev {
 			#[cfg(not(target_os = "linux"))]
 			DeviceArgument::ATA(_, id) => id,
 			DeviceArgument::SAT(_, id) => id,
 			DeviceArgument::SCSI(_) => unimplemented!(),
+			DeviceArgument::NVMe(_, _) => unreachable!(),
 		};
 
-		let use_json = args.is_present("json");
-
 		when_smart_enabled(&id.smart, "health status", || {
 			let status = match dev {
 				#[cfg(not(target_os = "linux"))]
 				DeviceArgument::ATA(dev, _) => dev.get_smart_health().unwrap(),
 				DeviceArgument::SAT(dev, _) => dev.get_smart_health().unwrap(),
 				DeviceArgument::SCSI(_) => unimplemented!(),
+				DeviceArgument::NVMe(_, _) => unreachable!(),
 			};
 
-			if use_json {
-				print!("{}\n", serde_json::to_string(&status.to_json().unwrap()).unwrap());
-			} else {
-				print!("S.M.A.R.T. health status: {}\n", match status {
-					Some(true) => "good",
-					Some(false) => "BAD",
-					None => "(unknown)",
-				});
-			}
+			print_health(status, use_json);
+		});
+	}
+}
+
+fn print_health(status: Option<bool>, use_json: bool) {
+	if use_json {
+		print!("{}\n", serde_json::to_string(&status.to_json().unwrap()).unwrap());
+	} else {
+		print!("S.M.A.R.T. health status: {}\n", match status {
+			Some(true) => "good",
+			Some(false) => "BAD",
+			None => "(unknown)",
 		});
 	}
 }
@@ -2,6 +2,8 @@ use hdd::ata::data::id;
 use hdd::drivedb;
 use hdd::scsi::SCSICommon;
 use hdd::scsi::data::inquiry;
+use hdd::nvme::data::id as nvme_id;
+use hdd::nvme::NVMeCommon;
 
 use clap::{
 	ArgMatches,
@@ -91,6 +93,7 @@ fn print_ata_id(id: &id::Id, meta: &Option<drivedb::DriveMeta>) {
 	print!("S.M.A.R.T.:    {}\n", id.smart);
 	print!("Error logging: {}\n", bool_to_sup(id.smart_error_logging_supported));
 	print!("Self-test:     {}\n", bool_to_sup(id.smart_self_test_supported));
+	print!("SCT:           {}\n", bool_to_sup(id.sct.supported));
 
 	print!("\n");
 }
@@ -103,6 +106,73 @@ fn print_scsi_id(inquiry: &inquiry::Inquiry) {
 	// TODO other inquiry fields, capacity, …
 }
 
+fn print_nvme_id(id: &nvme_id::Controller, ns: &Option<nvme_id::Namespace>) {
+	fn capacity(bytes: u64) -> String {
+		format!("{} bytes ({}, {})",
+			bytes.separated_string(),
+			match decimal_prefix(bytes as f32) {
+				Prefixed(p, x) => format!("{:.1} {}B", x, p),
+				Standalone(x)  => format!("{} bytes", x),
+			},
+			match binary_prefix(bytes as f32) {
+				Prefixed(p, x) => format!("{:.1} {}B", x, p),
+				Standalone(x)  => format!("{} bytes", x),
+			},
+		)
+	}
+
+	print!("Model:    {}\n", id.model);
+	print!("Firmware: {}\n", id.firmware);
+	print!("Serial:   {}\n", id.serial);
+	print!("PCI vendor: 0x{:04x}, subsystem vendor: 0x{:04x}\n", id.vendor_id, id.subsystem_vendor_id);
+	print!("NVMe version: {}\n", match id.version {
+		Some((major, minor, tertiary)) => format!("{}.{}.{}", major, minor, tertiary),
+		None => "1.1 or earlier".to_string(),
+	});
+	if !id.subsystem_nqn.is_empty() {
+		print!("Subsystem NQN: {}\n", id.subsystem_nqn);
+	}
+
+	print!("\n");
+
+	if id.total_capacity != 0 {
+		print!("Total capacity:       {}\n", capacity(id.total_capacity));
+		print!("Unallocated capacity: {}\n", capacity(id.unallocated_capacity));
+	}
+	print!("Namespaces: {}\n", id.namespaces);
+
+	if let Some(ns) = ns {
+		let block_size = ns.block_size().unwrap_or(0);
+		print!("Namespace size:       {}\n", capacity(ns.size * block_size));
+		print!("Namespace block size: {}\n", block_size);
+	}
+
+	print!("\n");
+
+	// The following guide, when printed, is exactly 80 characters
+	// ... "..............................................................supported disabled\n"
+	print!("Volatile write cache:          {}\n", if id.volatile_write_cache { "present" } else { "not present" });
+	print!("Self-test:                     {}\n", bool_to_sup(id.admin_commands.self_test));
+	print!("Security send/receive:         {}\n", bool_to_sup(id.admin_commands.security));
+	print!("Format NVM:                    {}\n", bool_to_sup(id.admin_commands.format_nvm));
+	print!("Namespace management:          {}\n", bool_to_sup(id.admin_commands.namespace_management));
+	print!("Dataset management (TRIM):     {}\n", bool_to_sup(id.nvm_commands.dataset_management));
+	print!("Write zeroes:                  {}\n", bool_to_sup(id.nvm_commands.write_zeroes));
+
+	print!("\n");
+
+	if let Some(t) = id.warning_temp {
+		print!("Warning temperature:  {} °C\n", t as i32 - 273);
+	}
+	if let Some(t) = id.critical_temp {
+		print!("Critical temperature: {} °C\n", t as i32 - 273);
+	}
+	print!("Error log entries: {}\n", id.error_log_entries);
+	print!("Firmware slots:    {}{}\n", id.firmware_slots, if id.firmware_slot1_readonly { " (slot 1 is read-only)" } else { "" });
+
+	print!("\n");
+}
+
 pub struct Info {}
 impl Subcommand for Info {
 	fn subcommand(&self) -> App<'static, 'static> {
@@ -129,6 +199,7 @@ impl Subcommand for Info {
 			DeviceArgument::ATA(_, id) => Some(id),
 			DeviceArgument::SAT(_, id) => Some(id),
 			DeviceArgument::SCSI(_) => None,
+			DeviceArgument::NVMe(_, _) => None,
 		};
 
 		let use_json = args.is_present("json");
@@ -145,6 +216,22 @@ impl Subcommand for Info {
 			}
 		}
 
+		if let DeviceArgument::NVMe(dev, id) = dev {
+			// namespace devices (e.g. /dev/nvme0n1) also tell us about the namespace; controller devices (e.g. /dev/nvme0) do not
+			let ns = dev.namespace_id().unwrap()
+				.map(|nsid| dev.identify_namespace(nsid).unwrap());
+
+			if use_json {
+				let mut info = id.to_json().unwrap();
+				if let Some(ns) = &ns {
+					info.as_object_mut().unwrap().insert("namespace".to_string(), ns.to_json().unwrap());
+				}
+				print!("{}\n", serde_json::to_string(&info).unwrap());
+			} else {
+				print_nvme_id(id, &ns);
+			}
+		}
+
 		if let Some(id) = ata_id {
 			let drivedb = open_drivedb(args.values_of("drivedb"));
 			let meta = drivedb.as_ref().map(|drivedb| drivedb.render_meta(
@@ -1,4 +1,4 @@
-use hdd::device::list_devices;
+use hdd::device::{list_devices_info, DeviceInfo};
 
 use clap::{
 	ArgMatches,
@@ -8,6 +8,13 @@ use clap::{
 
 use serde_json;
 
+use number_prefix::{decimal_prefix, Standalone, Prefixed};
+
+use prettytable;
+use prettytable::Table;
+use prettytable::row::Row;
+use prettytable::cell::Cell;
+
 use ::DeviceArgument;
 use super::{Subcommand, arg_json};
 
@@ -33,7 +40,7 @@ impl Subcommand for List {
 			::std::process::exit(1);
 		};
 
-		let devs = list_devices().unwrap_or_else(|err| {
+		let devs = list_devices_info().unwrap_or_else(|err| {
 			eprint!("Cannot list devices: {}\n", err);
 			::std::process::exit(1);
 		});
@@ -41,9 +48,53 @@ impl Subcommand for List {
 		if args.is_present("json") {
 			print!("{}\n", serde_json::to_string(&devs).unwrap());
 		} else {
-			for dev in devs {
-				print!("{}\n", dev.into_os_string().to_str().unwrap());
-			}
+			print_human(&devs);
+		}
+	}
+}
+
+fn print_human(devs: &[DeviceInfo]) {
+	fn opt<T: ToString>(val: &Option<T>) -> String {
+		val.as_ref().map(|v| v.to_string()).unwrap_or_else(|| "-".to_string())
+	}
+	fn flag(val: Option<bool>, yes: &str) -> String {
+		match val {
+			Some(true) => yes.to_string(),
+			Some(false) => "".to_string(),
+			None => "-".to_string(),
 		}
 	}
+
+	let mut table = Table::new();
+	table.set_format(*prettytable::format::consts::FORMAT_CLEAN);
+
+	table.set_titles(Row::new(
+		["Device", "Type", "Vendor", "Model", "Serial", "Size", "", "H:C:T:L", "Driver", "Block", "Generic"].iter()
+			.map(|title| Cell::new(title))
+			.collect()
+	));
+
+	for dev in devs {
+		table.add_row(Row::new(vec![
+			Cell::new(&dev.path.to_string_lossy()),
+			Cell::new(&opt(&dev.transport.map(|t| format!("{:?}", t)))),
+			Cell::new(&opt(&dev.vendor)),
+			Cell::new(&opt(&dev.model)),
+			Cell::new(&opt(&dev.serial)),
+			Cell::new(&opt(&dev.size.map(|size| match decimal_prefix(size as f32) {
+				Prefixed(p, x) => format!("{:.1} {}B", x, p),
+				Standalone(x)  => format!("{} B", x),
+			}))).style_spec("r"),
+			Cell::new([
+				flag(dev.rotational.map(|r| !r), "SSD"),
+				flag(dev.removable, "RM"),
+			].join(" ").trim()),
+			Cell::new(&opt(&dev.address.map(|a| format!("{}:{}:{}:{}", a.host, a.channel, a.target, a.lun)))),
+			Cell::new(&opt(&dev.driver)),
+			Cell::new(&opt(&dev.block)),
+			Cell::new(&opt(&dev.generic)),
+		]));
+	}
+
+	table.printstd();
 }
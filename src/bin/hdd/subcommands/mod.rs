@@ -2,6 +2,8 @@ mod info;
 mod health;
 mod attrs;
 mod list;
+mod errorlog;
+mod sct;
 
 use std::collections::HashMap;
 use clap::{self, App, ArgMatches};
@@ -45,6 +47,8 @@ lazy_static! {
 		m.insert("list",   &list::List {});
 		m.insert("info",   &info::Info {});
 		m.insert("attrs",  &attrs::Attrs {});
+		m.insert("errorlog", &errorlog::ErrorLogCmd {});
+		m.insert("sct", &sct::SCTCmd {});
 		m
 	};
 }
@@ -0,0 +1,175 @@
+use hdd::ata::misc::Misc;
+use hdd::ata::data::sct::{ERCTimer, SCTStatus, TemperatureHistory};
+
+use clap::{
+	Arg,
+	ArgMatches,
+	App,
+	SubCommand,
+};
+
+use serde_json;
+use serde_json::value::ToJson;
+
+use ::{DeviceArgument, when_smart_enabled};
+use super::{Subcommand, arg_json};
+
+use std::path::Path;
+
+fn format_temp(t: Option<i8>) -> String {
+	t.map(|t| format!("{}°C", t)).unwrap_or("-".to_string())
+}
+
+fn format_erc(value: u16) -> String {
+	if value == 0 {
+		"disabled".to_string()
+	} else {
+		format!("{}.{} s", value / 10, value % 10)
+	}
+}
+
+fn print_status(status: &SCTStatus) {
+	print!("SCT status (format version {}, SCT version {:#06x}, spec version {}):\n", status.format_version, status.sct_version, status.spec_version);
+	print!("Device state:                {:?}\n", status.device_state);
+	print!("Current temperature:         {}\n", format_temp(status.temperature));
+	print!("Power cycle min/max:         {} / {}\n", format_temp(status.min_temperature), format_temp(status.max_temperature));
+	print!("Lifetime min/max:            {} / {}\n", format_temp(status.lifetime_min_temperature), format_temp(status.lifetime_max_temperature));
+	print!("Over/under temperature time: {} / {} min\n", status.over_limit_count, status.under_limit_count);
+}
+
+fn print_history(history: &TemperatureHistory) {
+	print!("Temperature history (sampling period: {} min, interval: {} min):\n", history.sampling_period, history.interval);
+	print!("Operating temperature range: {} to {}\n", format_temp(history.min_operating_temperature), format_temp(history.max_operating_temperature));
+	print!("Temperature limits:          {} to {}\n", format_temp(history.under_limit_temperature), format_temp(history.over_limit_temperature));
+	print!("Oldest to newest:\n");
+	for chunk in history.history.chunks(16) {
+		let line: Vec<_> = chunk.iter()
+			.map(|t| t.map(|t| format!("{:3}", t)).unwrap_or("  -".to_string()))
+			.collect();
+		print!("  {}\n", line.join(" "));
+	}
+}
+
+fn parse_erc(s: &str) -> Option<(u16, u16)> {
+	let mut values = s.splitn(2, ',').map(|x| x.parse::<u16>());
+	match (values.next(), values.next()) {
+		(Some(Ok(read)), Some(Ok(write))) => Some((read, write)),
+		(Some(Ok(both)), None) => Some((both, both)),
+		_ => None,
+	}
+}
+
+pub struct SCTCmd {}
+impl Subcommand for SCTCmd {
+	fn subcommand(&self) -> App<'static, 'static> {
+		SubCommand::with_name("sct")
+			.about("Prints SCT status, temperature history and error recovery control timers")
+			.arg(Arg::with_name("set-erc")
+				.long("set-erc")
+				.takes_value(true)
+				.value_name("read[,write]")
+				.help("set error recovery control timers, in tenths of a second (0 disables the timer); note that these values are lost when the device is power-cycled")
+			)
+			.arg(arg_json())
+	}
+
+	fn run(
+		&self,
+		_: &Option<&Path>,
+		dev: &Option<&DeviceArgument>,
+		args: &ArgMatches,
+	) {
+		let dev = dev.unwrap_or_else(|| {
+			// TODO show usage and whatnot
+			eprint!("<device> is required\n");
+			::std::process::exit(1);
+		});
+
+		let (misc, id): (&Misc, _) = match dev {
+			#[cfg(not(target_os = "linux"))]
+			DeviceArgument::ATA(dev, id) => (dev, id),
+			DeviceArgument::SAT(dev, id) => (dev, id),
+			DeviceArgument::SCSI(_) | DeviceArgument::NVMe(_, _) => {
+				eprint!("SCT is only available for ATA devices\n");
+				::std::process::exit(1);
+			},
+		};
+
+		if ! id.sct.supported {
+			eprint!("SCT is not supported\n");
+			::std::process::exit(1);
+		}
+
+		let use_json = args.is_present("json");
+		let set_erc = args.value_of("set-erc").map(|s| parse_erc(s).unwrap_or_else(|| {
+			eprint!("invalid error recovery control timers: {}\n", s);
+			::std::process::exit(1);
+		}));
+
+		// SCT commands are issued through SMART logs
+		when_smart_enabled(&id.smart, "SCT", || {
+			if let Some((read, write)) = set_erc {
+				if ! id.sct.error_recovery_control {
+					eprint!("SCT Error Recovery Control is not supported\n");
+					::std::process::exit(1);
+				}
+				for &(timer, value) in &[(ERCTimer::Read, read), (ERCTimer::Write, write)] {
+					misc.set_sct_erc(timer, value).unwrap_or_else(|err| {
+						eprint!("Cannot set error recovery control timer: {}\n", err);
+						::std::process::exit(1);
+					});
+				}
+			}
+
+			let status = misc.get_sct_status()
+				.map_err(|err| eprint!("Cannot read SCT status: {}\n", err))
+				.ok();
+
+			let erc = if id.sct.error_recovery_control {
+				let read = misc.get_sct_erc(ERCTimer::Read);
+				let write = misc.get_sct_erc(ERCTimer::Write);
+				match (read, write) {
+					(Ok(read), Ok(write)) => Some((read, write)),
+					(Err(err), _) | (_, Err(err)) => {
+						eprint!("Cannot read error recovery control timers: {}\n", err);
+						None
+					},
+				}
+			} else { None };
+
+			let history = if id.sct.data_tables {
+				misc.get_sct_temperature_history()
+					.map_err(|err| eprint!("Cannot read SCT temperature history: {}\n", err))
+					.ok()
+			} else { None };
+
+			if use_json {
+				let mut json = serde_json::Map::new();
+				json.insert("status".to_string(), status.to_json().unwrap());
+				json.insert("erc".to_string(), erc.map(|(read, write)| {
+					let mut erc = serde_json::Map::new();
+					erc.insert("read".to_string(), read.to_json().unwrap());
+					erc.insert("write".to_string(), write.to_json().unwrap());
+					erc
+				}).to_json().unwrap());
+				json.insert("temperature-history".to_string(), history.to_json().unwrap());
+				print!("{}\n", serde_json::to_string(&json).unwrap());
+				return;
+			}
+
+			if let Some(status) = status {
+				print_status(&status);
+			}
+			if let Some((read, write)) = erc {
+				print!("\n");
+				print!("Error recovery control:\n");
+				print!("Read:  {}\n", format_erc(read));
+				print!("Write: {}\n", format_erc(write));
+			}
+			if let Some(history) = history {
+				print!("\n");
+				print_history(&history);
+			}
+		});
+	}
+}
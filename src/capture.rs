@@ -0,0 +1,166 @@
+/*!
+Recording commands sent to the device, and replaying them later.
+
+Wrap any device with [`Recorder`](struct.Recorder.html) to keep track of every command it executes along with the replies, then save that as a [`Capture`](struct.Capture.html) (e.g. with serde, see `serializable` feature).
+Later on, [`Capture::replay`](struct.Capture.html#method.replay) turns it into a [`MockDevice`](../mock/struct.MockDevice.html) that answers the very same commands in the very same way, no hardware required.
+
+## Example
+
+```
+use hdd::Device;
+use hdd::scsi::{SCSIDevice, SCSICommon};
+use hdd::capture::{Recorder, Capture};
+
+let dev = Recorder::new(SCSIDevice::new(Device::open("/dev/da0").unwrap()));
+let log = dev.log();
+
+let (sense, data) = dev.scsi_inquiry(false, 0).unwrap();
+
+let capture = Capture {
+	device: Some("/dev/da0".to_string()),
+	entries: log.borrow().clone(),
+};
+
+...
+
+let dev = capture.replay();
+assert_eq!(dev.scsi_inquiry(false, 0).unwrap(), (sense, data));
+```
+*/
+
+use std::rc::Rc;
+use std::cell::RefCell;
+use std::time::Duration;
+
+use Direction;
+use ata::{ATATransport, Protocol, RegistersRead, RegistersWrite, RegistersReadExt, RegistersWriteExt};
+use scsi::{self, SCSICommon};
+use mock::{MockDevice, Failure, sent_data};
+
+/// Single command (along with the data sent to the device, if any) and the reply from the device, or the reason the command failed
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serializable", derive(Serialize, Deserialize))]
+pub enum Entry {
+	SCSI {
+		cmd: Vec<u8>,
+		data_out: Vec<u8>,
+		/// Sense and data
+		reply: Result<(Vec<u8>, Vec<u8>), Failure>,
+	},
+	ATA {
+		proto: Protocol,
+		regs: RegistersWrite,
+		data_out: Vec<u8>,
+		/// Registers and data
+		reply: Result<(RegistersRead, Vec<u8>), Failure>,
+	},
+	ATAExt {
+		proto: Protocol,
+		regs: RegistersWriteExt,
+		data_out: Vec<u8>,
+		/// Registers and data
+		reply: Result<(RegistersReadExt, Vec<u8>), Failure>,
+	},
+}
+
+/// List of recorded commands, in order they were executed, as it is meant to be saved to or read from capture files
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serializable", derive(Serialize, Deserialize))]
+pub struct Capture {
+	/// Path to the device commands were sent to, if known
+	pub device: Option<String>,
+	pub entries: Vec<Entry>,
+}
+
+impl Capture {
+	/// Returns device that replies to recorded commands with recorded answers; if the same command was executed several times, it gets recorded answers in the same order.
+	pub fn replay(&self) -> MockDevice {
+		let mut dev = MockDevice::new();
+		for entry in &self.entries {
+			match entry {
+				Entry::SCSI { cmd, data_out, reply: Ok((sense, data)) } => dev.add_scsi(cmd, data_out, sense, data),
+				Entry::SCSI { cmd, data_out, reply: Err(failure) } => dev.add_scsi_failure(cmd, data_out, failure.clone()),
+				Entry::ATA { proto, regs, data_out, reply: Ok((reply, data)) } => dev.add_ata(*proto, regs.clone(), data_out, reply.clone(), data),
+				Entry::ATA { proto, regs, data_out, reply: Err(failure) } => dev.add_ata_failure(*proto, regs.clone(), data_out, failure.clone()),
+				Entry::ATAExt { proto, regs, data_out, reply: Ok((reply, data)) } => dev.add_ata_ext(*proto, regs.clone(), data_out, reply.clone(), data),
+				Entry::ATAExt { proto, regs, data_out, reply: Err(failure) } => dev.add_ata_ext_failure(*proto, regs.clone(), data_out, failure.clone()),
+			}
+		}
+		dev
+	}
+}
+
+/**
+Wrapper that keeps every executed command along with the reply.
+
+Failed commands are recorded as well (see [`Failure`](../mock/enum.Failure.html)), so replayed device fails in the same way; note that replayed I/O errors only retain OS error code and the message.
+*/
+#[derive(Debug)]
+pub struct Recorder<T> {
+	device: T,
+	log: Rc<RefCell<Vec<Entry>>>,
+}
+
+impl<T> Recorder<T> {
+	pub fn new(device: T) -> Self {
+		Self {
+			device,
+			log: Rc::new(RefCell::new(vec![])),
+		}
+	}
+
+	/// Returns list of recorded entries. This list is shared with the recorder, so it is possible to read it after the recorder is boxed or consumed by some other wrapper.
+	pub fn log(&self) -> Rc<RefCell<Vec<Entry>>> {
+		self.log.clone()
+	}
+
+	/// Return the wrapped device.
+	pub fn unwrap(self) -> T {
+		self.device
+	}
+}
+
+impl<T: SCSICommon> SCSICommon for Recorder<T> {
+	fn do_cmd(&self, cmd: &[u8], dir: Direction, sense_len: usize, data_len: usize, data_out: &[u8], timeout: Option<Duration>) -> Result<(Vec<u8>, Vec<u8>), scsi::Error> {
+		let ret = self.device.do_cmd(cmd, dir, sense_len, data_len, data_out, timeout);
+		self.log.borrow_mut().push(Entry::SCSI {
+			cmd: cmd.to_vec(),
+			data_out: sent_data(dir, data_out).to_vec(),
+			reply: match &ret {
+				Ok(reply) => Ok(reply.clone()),
+				Err(err) => Err(Failure::from(err)),
+			},
+		});
+		ret
+	}
+}
+
+impl<T: ATATransport> ATATransport for Recorder<T> {
+	fn do_ata_cmd(&self, proto: Protocol, dir: Direction, regs: &RegistersWrite, data_out: &[u8], timeout: Option<Duration>) -> Result<(RegistersRead, Vec<u8>), scsi::ATAError> {
+		let ret = self.device.do_ata_cmd(proto, dir, regs, data_out, timeout);
+		self.log.borrow_mut().push(Entry::ATA {
+			proto,
+			regs: regs.clone(),
+			data_out: sent_data(dir, data_out).to_vec(),
+			reply: match &ret {
+				Ok(reply) => Ok(reply.clone()),
+				Err(err) => Err(Failure::from(err)),
+			},
+		});
+		ret
+	}
+
+	fn do_ata_cmd_ext(&self, proto: Protocol, dir: Direction, regs: &RegistersWriteExt, data_out: &[u8], timeout: Option<Duration>) -> Result<(RegistersReadExt, Vec<u8>), scsi::ATAError> {
+		let ret = self.device.do_ata_cmd_ext(proto, dir, regs, data_out, timeout);
+		self.log.borrow_mut().push(Entry::ATAExt {
+			proto,
+			regs: regs.clone(),
+			data_out: sent_data(dir, data_out).to_vec(),
+			reply: match &ret {
+				Ok(reply) => Ok(reply.clone()),
+				Err(err) => Err(Failure::from(err)),
+			},
+		});
+		ret
+	}
+}
@@ -2,24 +2,35 @@ use cam::{CAMDevice, CCB, error};
 use cam::bindings::*;
 use std::io;
 use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use super::{DEFAULT_TIMEOUT, DeviceInfo};
 
 /// See [parent module docs](../index.html)
 #[derive(Debug)]
 pub struct Device {
 	pub(crate) dev: CAMDevice,
+	pub(crate) timeout: Duration,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serializable", derive(Serialize))]
 pub enum Type { ATA, SCSI }
 
 impl Device {
 	pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, io::Error> {
 		Ok(Device {
 			dev: CAMDevice::open(path.as_ref().as_os_str())?,
+			timeout: Duration::from_secs(DEFAULT_TIMEOUT),
 		})
 	}
 
 	pub fn get_type(&self) -> Result<Type, io::Error> {
+		self.get_type_reason().map(|(t, _)| t)
+	}
+
+	/// Returns device type, along with the human-readable explanation of how that type was determined.
+	pub fn get_type_reason(&self) -> Result<(Type, String), io::Error> {
 		unsafe {
 			let ccb: CCB = CCB::new(&self.dev);
 			ccb.ccb_h().func_code = xpt_opcode_XPT_PATH_INQ;
@@ -30,10 +41,11 @@ impl Device {
 				Err(error::from_status(&self.dev, &ccb))?
 			}
 
-			Ok(match ccb.cpi().protocol {
+			let protocol = ccb.cpi().protocol;
+			Ok(match protocol {
 				// TODO USB, SATA port multipliers and whatnot
-				cam_proto_PROTO_ATA => Type::ATA,
-				_ => Type::SCSI,
+				cam_proto_PROTO_ATA => (Type::ATA, "CAM protocol is ATA".to_string()),
+				_ => (Type::SCSI, format!("CAM protocol is {}", protocol)),
 			})
 		}
 	}
@@ -254,3 +266,13 @@ pub fn list_devices() -> Result<Vec<PathBuf>, io::Error> {
 
 	Ok(devices)
 }
+
+/**
+Lists devices currently presented in the system, along with the information OS provides about them.
+
+Currently it's only paths to the devices.
+*/
+// TODO inquiry data, serial numbers and sizes are all available from CAM (XPT_GDEV_TYPE, XPT_PATH_INQ), no need to send commands to the devices
+pub fn list_devices_info() -> Result<Vec<DeviceInfo>, io::Error> {
+	Ok(list_devices()?.into_iter().map(DeviceInfo::new).collect())
+}
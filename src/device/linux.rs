@@ -6,15 +6,35 @@ use std::os::unix::fs::OpenOptionsExt;
 use std::path::{Path, PathBuf};
 use std::io::{BufRead, BufReader};
 use std::collections::HashSet;
+use std::ffi::OsStr;
+use std::time::Duration;
+
+use super::{DEFAULT_TIMEOUT, DeviceInfo, Address};
 
 /// See [parent module docs](../index.html)
 #[derive(Debug)]
 pub struct Device {
 	pub(crate) file: File,
+	pub(crate) path: PathBuf,
+	pub(crate) timeout: Duration,
 }
 
-#[derive(Debug)]
-pub enum Type { SCSI }
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serializable", derive(Serialize))]
+pub enum Type {
+	/// Native SCSI device (e.g. SAS or FC disk), or something sysfs does not tell much about
+	SCSI,
+	/// ATA device handled by libata, which means it's accessible through SAT
+	SAT,
+	/// NVMe namespace or controller; SCSI commands are not supported
+	NVMe,
+	/// USB mass storage; SAT support depends on the bridge
+	USB,
+	/// virtio-scsi device; commands are passed to the hypervisor, which might or might not pass them to the real device
+	VirtioSCSI,
+	/// virtio-blk device; neither SCSI nor ATA commands are supported
+	VirtioBlk,
+}
 
 impl Device {
 	pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, io::Error> {
@@ -27,147 +47,436 @@ impl Device {
 				// this fixes access to optical drives and other ejectable media
 				// (https://github.com/vthriller/hdd-rs/issues/1)
 				.custom_flags(libc::O_NONBLOCK)
-				.open(path)?,
+				.open(&path)?,
+			path: path.as_ref().to_path_buf(),
+			timeout: Duration::from_secs(DEFAULT_TIMEOUT),
 		})
 	}
 
-	pub fn get_type(&self) -> Result<Type, io::Error> { Ok(Type::SCSI) }
+	pub fn get_type(&self) -> Result<Type, io::Error> {
+		self.get_type_reason().map(|(t, _)| t)
+	}
+
+	/**
+	Returns device type, along with the human-readable explanation of how that type was determined.
+
+	Type is inferred from sysfs (i.e. `/sys/class/{block,scsi_generic,nvme}/<name>`, see [`Enumerator::get_type_reason`](struct.Enumerator.html#method.get_type_reason)); devices unknown to sysfs are reported as `Type::SCSI`.
+	*/
+	pub fn get_type_reason(&self) -> Result<(Type, String), io::Error> {
+		// resolve things like /dev/disk/by-id/… into /dev/sdX
+		let path = self.path.canonicalize()?;
+		match path.file_name() {
+			Some(name) => Ok(Enumerator::new().get_type_reason(name)),
+			None => Ok((Type::SCSI, format!("cannot get device name from path {:?}", path))),
+		}
+	}
+}
+
+/// Reads a file from sysfs, trimming trailing newline (and, as it happens with SCSI INQUIRY strings, padding).
+fn read_sysfs_str(path: &Path) -> Option<String> {
+	fs::read_to_string(path).ok().map(|s| s.trim().to_string())
+}
+
+/// Returns name of the entity that the symlink points to, e.g. `virtio_scsi` for `…/device/driver`.
+fn read_sysfs_link(path: &Path) -> Option<String> {
+	path.read_link().ok()
+		.and_then(|link| link.file_name().map(|name| name.to_string_lossy().into_owned()))
 }
 
 /// Lists paths to devices currently presented in the system.
 pub fn list_devices() -> Result<Vec<PathBuf>, io::Error> {
-	/*
-	Various software enumerates block devices in a variety of ways:
-	- smartd: probes for /dev/hd[a-t], /dev/sd[a-z], /dev/sd[a-c][a-z], /dev/nvme[0-99]
-	- lsscsi: looks for *:* in /sys/bus/scsi/devices/, skipping {host,target}*
-	- sg3_utils/sg_scan: iterates over /sys/class/scsi_generic if exists, otherwise probing for /dev/sg{0..8191} or /dev/sg{a..z,aa..zz,...}
-	- util-linux/lsblk: iterates over /sys/block, skipping devices with major number 1 (RAM disks) by default (see --include/--exclude), as well as devices with no known size or the size of 0 (see /sys/class/block/<X>/size)
-	- udisks: queries udev for devices in a "block" subsystem
-	- gnome-disk-utility: just asks udisks
-	- udev: just reads a bunch of files from /sys, appending irrelevant (in our case) data from hwdb and attributes set via various rules
-
-	This code was once written using libudev, but it was dropped for a number of reason:
-	- it's an extra dependency
-	- it is much harder to make static builds for x86_64-unknown-linux-musl
-	- it might not work on exotic systems that run mdev or rely solely on devtmpfs
-	- data provided by libudev can be easily read from /sys
-	- the data that libudev does not provide (e.g. `device/generic` symlink target for SCSI block devices), well, needs to be read from /sys anyways, so in a long run it's not, like, super-convenient to use this library
-	*/
+	Enumerator::new().list()
+}
+
+/**
+Lists devices currently presented in the system, along with the information sysfs provides about them.
+
+This does not require any special privileges, and no commands are sent to the devices.
+*/
+pub fn list_devices_info() -> Result<Vec<DeviceInfo>, io::Error> {
+	Enumerator::new().list_info()
+}
+
+/**
+Finds devices by walking sysfs tree.
 
-	let mut devices = vec![];
-	let mut skip_generics = HashSet::new();
+Unless you need to inspect something other than `/sys` (e.g. a copy of sysfs tree from another machine), just use [`list_devices`](fn.list_devices.html) or [`list_devices_info`](fn.list_devices_info.html) instead.
+*/
+#[derive(Debug, Clone)]
+pub struct Enumerator {
+	root: PathBuf,
+}
 
-	// XXX do not return Err() if /sys/class/block does not exist but /sys/class/scsi_generic does, or vice versa
+impl Default for Enumerator {
+	fn default() -> Self {
+		Self::new()
+	}
+}
 
-	// N.B. log entries are indented relative to each other
+impl Enumerator {
+	pub fn new() -> Self {
+		Self::with_root("/sys")
+	}
 
-	info!("inspecting /sys/class/block");
-	for d in fs::read_dir("/sys/class/block")? {
-		let d = if let Ok(d) = d { d } else { continue };
+	/// Uses `root` instead of `/sys`. Device paths are still reported as `/dev/<name>`.
+	pub fn with_root<P: AsRef<Path>>(root: P) -> Self {
+		let root = root.as_ref();
+		Enumerator {
+			// sysfs symlinks are resolved into absolute paths, and these are compared against paths under the root
+			root: root.canonicalize().unwrap_or_else(|_| root.to_path_buf()),
+		}
+	}
 
-		// XXX this assumes that dir name equals to whatever `DEVNAME` is set to in the uevent file
-		// (and that `DEVNAME` is even present there)
-		let name = d.file_name();
-		let path = if let Ok(path) = d.path().canonicalize() { path } else {
-			debug!("  {:?}: unable to read canonical device path, skipping", name);
-			continue
+	/// See [`list_devices`](fn.list_devices.html).
+	pub fn list(&self) -> Result<Vec<PathBuf>, io::Error> {
+		Ok(self.list_info()?.into_iter().map(|dev| dev.path).collect())
+	}
+
+	/// Returns iterator over `<root>/class/<class>`, or `None` if there's no such class.
+	fn read_class(&self, class: &str) -> Result<Option<fs::ReadDir>, io::Error> {
+		let path = self.root.join("class").join(class);
+		info!("inspecting {:?}", path);
+		match fs::read_dir(&path) {
+			Ok(dir) => Ok(Some(dir)),
+			// e.g. sg module is not loaded, or there's no block devices at all
+			Err(ref e) if e.kind() == io::ErrorKind::NotFound => {
+				debug!("  {:?} does not exist, skipping", path);
+				Ok(None)
+			},
+			Err(e) => Err(e),
+		}
+	}
+
+	/**
+	Returns type of the device named `name` (e.g. `sda`), along with the human-readable explanation of how that type was determined; see [`Device::get_type_reason`](struct.Device.html#method.get_type_reason).
+
+	Devices that are not found in `<root>/class/{block,scsi_generic,nvme}` are reported as `Type::SCSI`.
+	*/
+	pub fn get_type_reason<S: AsRef<OsStr>>(&self, name: S) -> (Type, String) {
+		let name = name.as_ref();
+		if self.root.join("class/nvme").join(name).exists() {
+			return (Type::NVMe, format!("{:?} is NVMe controller", name));
+		}
+
+		let device = ["block", "scsi_generic"].iter()
+			.map(|class| self.root.join("class").join(class).join(name).join("device"))
+			.find(|device| device.exists());
+		let device = match device {
+			Some(device) => device,
+			None => return (Type::SCSI, format!("no {:?} device found in sysfs", name)),
 		};
-		debug!("  {:?} → {:?}", name, path);
-
-		// skip devices like /dev/{loop,ram,zram,md,fd}*
-		if path.starts_with("/sys/devices/virtual/") {
-			debug!("    virtual device, skipping");
-			continue;
-		}
-		// Path.starts_with only works with whole path components so it can't match …/floppy.0
-		// hence .to_str()
-		if path.as_path().to_str().unwrap().starts_with("/sys/devices/platform/floppy") {
-			debug!("    floppy device, skipping");
-			continue;
-		}
-		if name.to_str().unwrap().starts_with('v') {
-			// probably /dev/vdX, check whether it is a virtio-blk device
-			// N.B. we do NOT skip virtio_scsi devices due to LUN passthrough
-			if let Ok(driver) = path.join("device/driver").read_link() {
-				if driver.file_name() == Some(::std::ffi::OsStr::new("virtio_blk")) {
-					debug!("    virtio_blk device, skipping");
-					continue;
-				}
+		debug!("inspecting {:?}", device);
+
+		self.device_type(&device)
+	}
+
+	/// Determines `Type` of the device, given its `/sys/class/<class>/<name>/device` directory.
+	fn device_type(&self, device: &Path) -> (Type, String) {
+		if let Some(subsystem) = read_sysfs_link(&device.join("subsystem")) {
+			// nvme0n1/device is either nvme0 (class nvme), or, with native multipath, nvme-subsys0 (class nvme-subsystem)
+			if subsystem.starts_with("nvme") {
+				return (Type::NVMe, format!("device subsystem is {}", subsystem));
+			}
+		}
+
+		// e.g. /sys/devices/pci0000:00/0000:00:1f.2/ata1/host0/target0:0:0/0:0:0:0
+		let real_path = device.canonicalize().unwrap_or_else(|_| device.to_path_buf());
+		// only look at the path below the root, which itself might be named whatever
+		let components: Vec<String> = real_path.strip_prefix(&self.root).unwrap_or(&real_path).components()
+			.map(|c| c.as_os_str().to_string_lossy().into_owned())
+			.collect();
+		let has_component = |prefix: &str| components.iter().find(|c| c.starts_with(prefix)).cloned();
+
+		// usb-storage and uas devices live under the USB device they belong to
+		if let Some(c) = has_component("usb") {
+			return (Type::USB, format!("device is attached to USB bus {}", c));
+		}
+
+		// the same goes for virtio-scsi, where host adapter is a child of the virtio device, and for virtio-blk, where the virtio device is the disk itself
+		let virtio = components.iter().position(|c| c.starts_with("virtio") && c[6..].parse::<u32>().is_ok());
+		if let Some(i) = virtio {
+			let virtio_dir = self.root.join(components[.. i + 1].iter().collect::<PathBuf>());
+			let driver = read_sysfs_link(&virtio_dir.join("driver"));
+			let host = components[i + 1 ..].iter().find(|c| c.starts_with("host") && c[4..].parse::<u32>().is_ok());
+			match (driver.as_deref(), host) {
+				(Some("virtio_blk"), _) => return (Type::VirtioBlk, format!("driver of {} is virtio_blk", components[i])),
+				(_, Some(host)) => return (Type::VirtioSCSI, format!("device is attached to SCSI host {} of {}", host, components[i])),
+				(Some("virtio_scsi"), None) => return (Type::VirtioSCSI, format!("driver of {} is virtio_scsi", components[i])),
+				// some other virtio device, e.g. virtio-mmio; see what the rest of sysfs has to say
+				_ => (),
+			}
+		}
+
+		// libata always reports "ATA" as a vendor (see ata_scsiop_inq_std() in drivers/ata/libata-scsi.c)
+		let vendor = read_sysfs_str(&device.join("vendor"));
+		if vendor.as_deref() == Some("ATA") {
+			return (Type::SAT, match has_component("ata") {
+				Some(c) => format!("vendor is \"ATA\", device is attached to libata port {}", c),
+				// e.g. SATL in SAS HBA firmware
+				None => "vendor is \"ATA\"".to_string(),
+			});
+		}
+
+		// SCSI transport classes leave their traces in the device path
+		for &(prefix, transport) in &[
+			("end_device-", "SAS"),
+			("rport-", "FC"),
+			("session", "iSCSI"),
+		] {
+			if has_component(prefix).is_some() {
+				return (Type::SCSI, format!("device is attached via {} transport", transport));
 			}
-			// there are other ways to identify virtio devices;
-			// one of them relies on PCI vendor id (`device/vendor` should read `0x1af4`, Red Hat, Inc.)
-			// and device id (`../../../device` → 0x1001)
-		}
-
-		// $ grep -q '^DEVTYPE=disk$' /sys/class/block/sda/uevent
-		if let Ok(uevent) = File::open(path.join("uevent")) {
-			let mut is_disk = false;
-
-			let mut buf = BufReader::new(uevent);
-			for line in buf.lines() {
-				match &line {
-					Ok(s) if s.as_str() == "DEVTYPE=disk" => {
-						debug!("    {}", s);
-						is_disk = true;
-						break;
+		}
+
+		match (vendor, read_sysfs_link(&device.join("driver"))) {
+			(Some(vendor), Some(driver)) => (Type::SCSI, format!("vendor is {:?}, driver is {}", vendor, driver)),
+			(Some(vendor), None) => (Type::SCSI, format!("vendor is {:?}", vendor)),
+			(None, _) => (Type::SCSI, "no vendor information in sysfs".to_string()),
+		}
+	}
+
+	/// See [`list_devices_info`](fn.list_devices_info.html).
+	pub fn list_info(&self) -> Result<Vec<DeviceInfo>, io::Error> {
+		/*
+		Various software enumerates block devices in a variety of ways:
+		- smartd: probes for /dev/hd[a-t], /dev/sd[a-z], /dev/sd[a-c][a-z], /dev/nvme[0-99]
+		- lsscsi: looks for *:* in /sys/bus/scsi/devices/, skipping {host,target}*
+		- sg3_utils/sg_scan: iterates over /sys/class/scsi_generic if exists, otherwise probing for /dev/sg{0..8191} or /dev/sg{a..z,aa..zz,...}
+		- util-linux/lsblk: iterates over /sys/block, skipping devices with major number 1 (RAM disks) by default (see --include/--exclude), as well as devices with no known size or the size of 0 (see /sys/class/block/<X>/size)
+		- udisks: queries udev for devices in a "block" subsystem
+		- gnome-disk-utility: just asks udisks
+		- udev: just reads a bunch of files from /sys, appending irrelevant (in our case) data from hwdb and attributes set via various rules
+
+		This code was once written using libudev, but it was dropped for a number of reason:
+		- it's an extra dependency
+		- it is much harder to make static builds for x86_64-unknown-linux-musl
+		- it might not work on exotic systems that run mdev or rely solely on devtmpfs
+		- data provided by libudev can be easily read from /sys
+		- the data that libudev does not provide (e.g. `device/generic` symlink target for SCSI block devices), well, needs to be read from /sys anyways, so in a long run it's not, like, super-convenient to use this library
+		*/
+
+		let mut devices = vec![];
+		let mut skip_generics = HashSet::new();
+
+		let blocks = self.read_class("block")?;
+		let generics = self.read_class("scsi_generic")?;
+		let nvme_controllers = self.read_class("nvme")?;
+		if blocks.is_none() && generics.is_none() && nvme_controllers.is_none() {
+			return Err(io::Error::new(io::ErrorKind::NotFound, format!("neither block, scsi_generic nor nvme class found in {:?}", self.root)));
+		}
+
+		// N.B. log entries are indented relative to each other
+
+		for d in blocks.into_iter().flatten() {
+			let d = if let Ok(d) = d { d } else { continue };
+
+			// XXX this assumes that dir name equals to whatever `DEVNAME` is set to in the uevent file
+			// (and that `DEVNAME` is even present there)
+			let name = d.file_name();
+			let path = if let Ok(path) = d.path().canonicalize() { path } else {
+				debug!("  {:?}: unable to read canonical device path, skipping", name);
+				continue
+			};
+			debug!("  {:?} → {:?}", name, path);
+
+			// skip devices like /dev/{loop,ram,zram,md,fd}*
+			// with native NVMe multipath, however, namespaces (e.g. nvme0n1) belong to the subsystem rather than to any of its controllers, and the subsystem is a virtual device
+			if path.starts_with(self.root.join("devices/virtual")) && ! path.starts_with(self.root.join("devices/virtual/nvme-subsystem")) {
+				debug!("    virtual device, skipping");
+				continue;
+			}
+			// e.g. nvme0c0n1, which is a path to the multipath namespace through one particular controller, and which has no device node
+			if read_sysfs_str(&path.join("hidden")).as_deref() == Some("1") {
+				debug!("    hidden device, skipping");
+				continue;
+			}
+			// Path.starts_with only works with whole path components so it can't match …/floppy.0
+			// hence .to_str()
+			if path.as_path().to_str().unwrap().starts_with(self.root.join("devices/platform/floppy").to_str().unwrap()) {
+				debug!("    floppy device, skipping");
+				continue;
+			}
+			if name.to_str().unwrap().starts_with('v') {
+				// probably /dev/vdX, check whether it is a virtio-blk device
+				// N.B. we do NOT skip virtio_scsi devices due to LUN passthrough
+				if let Ok(driver) = path.join("device/driver").read_link() {
+					if driver.file_name() == Some(OsStr::new("virtio_blk")) {
+						debug!("    virtio_blk device, skipping");
+						continue;
 					}
-					Ok(s) if s.starts_with("DEVTYPE=") => {
-						debug!("    {}", s);
-						is_disk = false; // see first match arm
-						break;
+				}
+				// there are other ways to identify virtio devices;
+				// one of them relies on PCI vendor id (`device/vendor` should read `0x1af4`, Red Hat, Inc.)
+				// and device id (`../../../device` → 0x1001)
+			}
+
+			// $ grep -q '^DEVTYPE=disk$' /sys/class/block/sda/uevent
+			if let Ok(uevent) = File::open(path.join("uevent")) {
+				let mut is_disk = false;
+
+				let mut buf = BufReader::new(uevent);
+				for line in buf.lines() {
+					match &line {
+						Ok(s) if s.as_str() == "DEVTYPE=disk" => {
+							debug!("    {}", s);
+							is_disk = true;
+							break;
+						}
+						Ok(s) if s.starts_with("DEVTYPE=") => {
+							debug!("    {}", s);
+							is_disk = false; // see first match arm
+							break;
+						}
+						Ok(_) => (), // keep reading
+						Err(e) => {
+							debug!("    problem reading uevent file: {}", e);
+							break;
+						},
 					}
-					Ok(_) => (), // keep reading
-					Err(e) => {
-						debug!("    problem reading uevent file: {}", e);
-						break;
-					},
 				}
+
+				if ! is_disk {
+					debug!("    undisclosed block device type, or device is not a disk, skipping");
+					continue
+				}
+			} else {
+				debug!("    unable to determine device type, skipping");
+				continue;
 			}
 
-			if ! is_disk {
-				debug!("    undisclosed block device type, or device is not a disk, skipping");
-				continue
+			devices.push((name, path.clone()));
+
+			// e.g. `readlink /sys/class/block/sda/device/generic` → `scsi_generic/sg0`
+			if let Ok(generic_path) = path.join("device/generic").read_link() {
+				if let Some(generic_name) = generic_path.file_name() {
+					debug!("    found corresponding scsi_generic device {:?}", generic_name);
+					skip_generics.insert(generic_name.to_os_string());
+				}
 			}
-		} else {
-			debug!("    unable to determine device type, skipping");
-			continue;
 		}
 
-		devices.push(name);
+		/*
+		Some drivers (e.g. aacraid) also provide generic SCSI devices for disks behind hardware RAIDs;
+		these devices can be used to query SMART or SCSI logs from disks that are not represented with corresponding block devices
+		*/
 
-		// e.g. `readlink /sys/class/block/sda/device/generic` → `scsi_generic/sg0`
-		if let Ok(generic_path) = path.join("device/generic").read_link() {
-			if let Some(generic_name) = generic_path.file_name() {
-				debug!("    found corresponding scsi_generic device {:?}", generic_name);
-				skip_generics.insert(generic_name.to_os_string());
+		for d in generics.into_iter().flatten() {
+			let d = if let Ok(d) = d { d } else { continue };
+
+			let name = d.file_name();
+			debug!("  {:?}", name);
+
+			if ! skip_generics.contains(&name) {
+				devices.push((name, d.path()));
+			} else {
+				debug!("    already covered by corresponding block device, skipping");
 			}
 		}
+
+		// NVMe controllers (e.g. /dev/nvme0) accept admin commands just like namespaces do, and they're the only way to reach controllers that have no namespaces attached
+		for d in nvme_controllers.into_iter().flatten() {
+			let d = if let Ok(d) = d { d } else { continue };
+
+			let name = d.file_name();
+			debug!("  {:?}", name);
+			devices.push((name, d.path()));
+		}
+
+		Ok(devices
+			.into_iter()
+			.map(|(name, path)| self.device_info(&name.into_string().unwrap(), &path))
+			.collect()
+		)
 	}
 
-	/*
-	Some drivers (e.g. aacraid) also provide generic SCSI devices for disks behind hardware RAIDs;
-	these devices can be used to query SMART or SCSI logs from disks that are not represented with corresponding block devices
-	*/
+	/// Collects information about the device, given its name and its directory in sysfs (either in `block`, `scsi_generic` or `nvme` class).
+	fn device_info(&self, name: &str, path: &Path) -> DeviceInfo {
+		let mut info = DeviceInfo::new(PathBuf::from(format!("/dev/{}", name)));
+
+		let device = path.join("device");
 
-	info!("inspecting /sys/class/scsi_generic");
-	for d in fs::read_dir("/sys/class/scsi_generic")? {
-		let d = if let Ok(d) = d { d } else { continue };
+		if read_sysfs_link(&path.join("subsystem")).as_deref() == Some("nvme") {
+			// NVMe controller: unlike namespaces, it holds model and serial number itself, and `device` is the PCI device (or whatever the transport is)
+			info.model = read_sysfs_str(&path.join("model"));
+			info.serial = read_sysfs_str(&path.join("serial"));
+			info.transport = Some(Type::NVMe);
+			info.driver = read_sysfs_link(&device.join("driver"));
+			return info;
+		}
+		let is_block = path.join("size").exists(); // only block devices have size
 
-		let name = d.file_name();
-		debug!("  {:?}", name);
+		if is_block {
+			info.block = Some(name.to_string());
+			info.generic = read_sysfs_link(&device.join("generic"));
 
-		if ! skip_generics.contains(&name) {
-			devices.push(name);
+			// > Note that this is always in units of 512 bytes, regardless of the logical block size of the device
+			// ~ Documentation/block/stat.txt, or, rather, the lack of `queue/hw_sector_size` in size calculations in lsblk & co.
+			info.size = read_sysfs_str(&path.join("size"))
+				.and_then(|s| s.parse::<u64>().ok())
+				.map(|sectors| sectors * 512);
+			info.rotational = read_sysfs_str(&path.join("queue/rotational")).map(|s| s == "1");
+			info.removable = read_sysfs_str(&path.join("removable")).map(|s| s == "1");
 		} else {
-			debug!("    already covered by corresponding block device, skipping");
+			info.generic = Some(name.to_string());
+			// e.g. /sys/class/scsi_generic/sg0/device/block/sda
+			info.block = fs::read_dir(device.join("block")).ok()
+				.and_then(|mut dir| dir.next())
+				.and_then(|d| d.ok())
+				.map(|d| d.file_name().to_string_lossy().into_owned());
 		}
+
+		if ! device.exists() {
+			return info;
+		}
+
+		info.vendor = read_sysfs_str(&device.join("vendor"));
+		info.model = read_sysfs_str(&device.join("model"));
+		info.serial = read_sysfs_str(&device.join("serial"))
+			.or_else(|| fs::read(device.join("vpd_pg80")).ok().and_then(|vpd| parse_vpd_serial(&vpd)));
+		info.transport = Some(self.device_type(&device).0);
+
+		let real_path = device.canonicalize().unwrap_or_else(|_| device.clone());
+
+		// SCSI devices are named after their address, e.g. /sys/devices/…/host0/target0:0:0/0:0:0:0
+		info.address = real_path.file_name()
+			.and_then(|name| name.to_str())
+			.and_then(parse_address);
+
+		// for SCSI devices, driver of the device itself is just `sd` or `sr` or whatever; what we're interested in is the host adapter driver
+		let host = real_path.components()
+			.map(|c| c.as_os_str().to_string_lossy().into_owned())
+			.find(|c| c.starts_with("host") && c[4..].parse::<u32>().is_ok());
+		info.driver = host
+			.and_then(|host| read_sysfs_str(&self.root.join("class/scsi_host").join(host).join("proc_name")))
+			// e.g. nvme0n1/device is nvme0, and nvme0/device is the PCI device itself
+			.or_else(|| read_sysfs_link(&device.join("device/driver")))
+			.or_else(|| read_sysfs_link(&device.join("driver")));
+
+		info
+	}
+}
+
+/// Parses `H:C:T:L` string.
+fn parse_address(s: &str) -> Option<Address> {
+	let parts: Vec<_> = s.split(':').collect();
+	if parts.len() != 4 {
+		return None;
 	}
+	Some(Address {
+		host: parts[0].parse().ok()?,
+		channel: parts[1].parse().ok()?,
+		target: parts[2].parse().ok()?,
+		lun: parts[3].parse().ok()?,
+	})
+}
 
-	Ok(devices
-		.into_iter()
-		.map(|dev| PathBuf::from(
-			format!("/dev/{}", dev.into_string().unwrap())
-		))
-		.collect()
-	)
+/// Extracts serial number from the raw Unit Serial Number VPD page (80h).
+fn parse_vpd_serial(vpd: &[u8]) -> Option<String> {
+	if vpd.len() < 4 || vpd[1] != 0x80 {
+		return None;
+	}
+	let len = ((vpd[2] as usize) << 8) + vpd[3] as usize;
+	let serial = vpd.get(4 .. 4 + len)?;
+	let serial = String::from_utf8_lossy(serial).trim().to_string();
+	if serial.is_empty() { None } else { Some(serial) }
 }
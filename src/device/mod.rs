@@ -17,3 +17,86 @@ pub use self::linux::*;
 pub mod freebsd;
 #[cfg(target_os = "freebsd")]
 pub use self::freebsd::*;
+
+use std::time::Duration;
+use std::cmp::min;
+use std::path::PathBuf;
+
+/// Time limit for commands sent to a freshly opened device, in seconds
+pub const DEFAULT_TIMEOUT: u64 = 10;
+
+impl Device {
+	/// Sets the time limit for every command sent to this device, unless overridden for a specific command (see [`SCSIDevice::do_cmd`](../scsi/struct.SCSIDevice.html#method.do_cmd)).
+	pub fn set_timeout(&mut self, timeout: Duration) {
+		self.timeout = timeout;
+	}
+
+	pub fn timeout(&self) -> Duration {
+		self.timeout
+	}
+
+	/// Returns `timeout` (or device-wide timeout if that's `None`) in milliseconds, which is what both SG_IO and CAM expect.
+	pub(crate) fn timeout_ms(&self, timeout: Option<Duration>) -> u32 {
+		let timeout = timeout.unwrap_or(self.timeout);
+		let ms = timeout.as_secs() * 1000 + u64::from(timeout.subsec_millis());
+		// for SG_IO, u32::MAX means no timeout at all, which is a sensible thing to do with absurdly large values
+		min(ms, u64::from(u32::MAX)) as u32
+	}
+}
+
+/// SCSI address of the device, commonly referred to as H:C:T:L
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serializable", derive(Serialize))]
+pub struct Address {
+	pub host: u32,
+	pub channel: u32,
+	pub target: u32,
+	pub lun: u64,
+}
+
+/**
+Device as seen by the OS, along with whatever OS knows about it.
+
+Fields that the OS does not provide (or that do not make sense for this particular device) are set to `None`.
+*/
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serializable", derive(Serialize))]
+pub struct DeviceInfo {
+	/// Path to pass to [`Device::open`](struct.Device.html#method.open)
+	pub path: PathBuf,
+	pub vendor: Option<String>,
+	pub model: Option<String>,
+	pub serial: Option<String>,
+	/// In bytes
+	pub size: Option<u64>,
+	pub rotational: Option<bool>,
+	pub removable: Option<bool>,
+	pub address: Option<Address>,
+	/// Driver of the host adapter the device is attached to (e.g. `ahci`, `mpt3sas`, `nvme`)
+	pub driver: Option<String>,
+	/// Name of the block device (e.g. `sda`)
+	pub block: Option<String>,
+	/// Name of the corresponding SCSI generic device (e.g. `sg0`)
+	pub generic: Option<String>,
+	pub transport: Option<Type>,
+}
+
+impl DeviceInfo {
+	/// Returns `DeviceInfo` with every field but `path` set to `None`.
+	pub fn new(path: PathBuf) -> Self {
+		DeviceInfo {
+			path,
+			vendor: None,
+			model: None,
+			serial: None,
+			size: None,
+			rotational: None,
+			removable: None,
+			address: None,
+			driver: None,
+			block: None,
+			generic: None,
+			transport: None,
+		}
+	}
+}
@@ -82,7 +82,11 @@ mod cam;
 
 pub mod ata;
 pub mod scsi;
+pub mod nvme;
 
 pub mod drivedb;
 
+pub mod mock;
+pub mod capture;
+
 mod utils;
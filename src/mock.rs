@@ -0,0 +1,312 @@
+/*!
+In-memory device that replies to commands from pre-scripted tables.
+
+Use this to exercise porcelain layers (e.g. [`SCSIPages`](../scsi/pages/struct.SCSIPages.html) or [`Misc`](../ata/misc/trait.Misc.html)) without having real disks around.
+
+## Example
+
+```
+use hdd::mock::MockDevice;
+use hdd::ata::{ATADevice, Protocol, RegistersRead, RegistersWrite};
+use hdd::ata::misc::Misc;
+
+let mut id_data = vec![0; 512];
+id_data[510] = 0xa5; // checksum validity indicator
+id_data[511] = 0x5b; // checksum
+
+let mut dev = MockDevice::new();
+dev.add_ata(
+	Protocol::PIO,
+	RegistersWrite { command: 0xec, features: 0, sector_count: 1, sector: 1, cyl_low: 0, cyl_high: 0, device: 0 },
+	&[],
+	RegistersRead { error: 0, sector_count: 0, sector: 0, cyl_low: 0, cyl_high: 0, device: 0, status: 0x50 },
+	&id_data,
+);
+
+let dev = ATADevice::new(dev);
+let id = dev.get_device_id().unwrap();
+assert!(id.is_ata);
+```
+*/
+
+use std::collections::HashMap;
+use std::cell::RefCell;
+use std::hash::Hash;
+use std::io;
+use std::time::Duration;
+
+use Direction;
+use ata::{self, ATATransport, Protocol, RegistersRead, RegistersWrite, RegistersReadExt, RegistersWriteExt};
+use scsi::{self, SCSICommon};
+use scsi::data::sense::key::SenseKey;
+use scsi::status::{HostStatus, DriverStatus};
+
+/**
+Command that failed instead of returning a reply, in a form that can be scripted, saved to capture files and turned back into an error.
+
+Converts from and into [`scsi::Error`](../scsi/enum.Error.html) and [`scsi::ATAError`](../scsi/enum.ATAError.html).
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serializable", derive(Serialize, Deserialize))]
+pub enum Failure {
+	/// `io::Error`, along with OS error code (if any) and error message
+	IO {
+		errno: Option<i32>,
+		message: String,
+	},
+	/// `scsi::Error::Sense`, with numeric sense key
+	Sense {
+		key: u8,
+		asc: u8,
+		ascq: u8,
+	},
+	Nonsense,
+	/// `scsi::Error::Host`, with `sg_io_hdr.host_status` value
+	Host(u16),
+	/// `scsi::Error::Driver`, with `sg_io_hdr.driver_status` value
+	Driver(u8),
+	/// `scsi::ATAError::NotSupported`
+	NotSupported,
+	/// `scsi::ATAError::NoRegisters`
+	NoRegisters,
+	/// `scsi::ATAError::TruncatedRegisters`
+	TruncatedRegisters,
+	/// `scsi::ATAError::InvalidTransfer`
+	InvalidTransfer,
+}
+
+impl From<&scsi::Error> for Failure {
+	fn from(err: &scsi::Error) -> Self {
+		use scsi::Error::*;
+		match *err {
+			IO(ref err) => Failure::IO {
+				errno: err.raw_os_error(),
+				message: err.to_string(),
+			},
+			Sense(key, asc, ascq) => Failure::Sense { key: key as u8, asc, ascq },
+			Nonsense => Failure::Nonsense,
+			Host(status) => Failure::Host(status.code()),
+			Driver(status) => Failure::Driver(status.code()),
+		}
+	}
+}
+
+impl From<&scsi::ATAError> for Failure {
+	fn from(err: &scsi::ATAError) -> Self {
+		use scsi::ATAError::*;
+		match *err {
+			SCSI(ref err) => Failure::from(err),
+			NotSupported => Failure::NotSupported,
+			NoRegisters => Failure::NoRegisters,
+			TruncatedRegisters => Failure::TruncatedRegisters,
+			InvalidTransfer => Failure::InvalidTransfer,
+		}
+	}
+}
+
+impl From<Failure> for scsi::Error {
+	fn from(failure: Failure) -> Self {
+		use self::Failure::*;
+		match failure {
+			IO { errno: Some(errno), .. } => scsi::Error::IO(io::Error::from_raw_os_error(errno)),
+			IO { errno: None, message } => scsi::Error::IO(io::Error::other(message)),
+			Sense { key, asc, ascq } => scsi::Error::Sense(SenseKey::from(key & 0x0f), asc, ascq),
+			Nonsense => scsi::Error::Nonsense,
+			Host(status) => scsi::Error::Host(HostStatus::from(status)),
+			Driver(status) => scsi::Error::Driver(DriverStatus::from(u16::from(status))),
+			// these are not something SCSI commands can fail with
+			NotSupported | NoRegisters | TruncatedRegisters | InvalidTransfer => scsi::Error::IO(io::Error::other(format!("unexpected failure of SCSI command: {:?}", failure))),
+		}
+	}
+}
+
+impl From<Failure> for scsi::ATAError {
+	fn from(failure: Failure) -> Self {
+		match failure {
+			Failure::NotSupported => scsi::ATAError::NotSupported,
+			Failure::NoRegisters => scsi::ATAError::NoRegisters,
+			Failure::TruncatedRegisters => scsi::ATAError::TruncatedRegisters,
+			Failure::InvalidTransfer => scsi::ATAError::InvalidTransfer,
+			failure => scsi::ATAError::SCSI(failure.into()),
+		}
+	}
+}
+
+// replies to the same command, in order they are to be sent
+#[derive(Debug)]
+struct Script<K: Eq + Hash, V> {
+	replies: HashMap<K, Vec<Result<V, Failure>>>,
+	// how many times each command was executed so far
+	served: RefCell<HashMap<K, usize>>,
+}
+
+impl<K: Eq + Hash, V> Default for Script<K, V> {
+	fn default() -> Self {
+		Self {
+			replies: HashMap::new(),
+			served: RefCell::new(HashMap::new()),
+		}
+	}
+}
+
+impl<K: Eq + Hash + Clone, V: Clone> Script<K, V> {
+	fn add(&mut self, key: K, reply: Result<V, Failure>) {
+		self.replies.entry(key).or_default().push(reply);
+	}
+
+	// once all the replies are served, the last one is repeated over and over again
+	fn next(&self, key: &K) -> Option<Result<V, Failure>> {
+		let replies = self.replies.get(key)?;
+		let mut served = self.served.borrow_mut();
+		let n = served.entry(key.clone()).or_insert(0);
+		let reply = replies[(*n).min(replies.len() - 1)].clone();
+		*n += 1;
+		Some(reply)
+	}
+
+	fn is_empty(&self) -> bool {
+		self.replies.is_empty()
+	}
+}
+
+/// Returns data that is actually sent to the device with a command going in direction `dir`.
+pub(crate) fn sent_data(dir: Direction, data_out: &[u8]) -> &[u8] {
+	match dir {
+		Direction::To | Direction::Both => data_out,
+		Direction::None | Direction::From => &[],
+	}
+}
+
+/**
+Device that answers commands with replies that were scripted beforehand.
+
+Commands are matched by everything that is sent to the device: CDB or registers, ATA protocol, and the data (which is always empty for commands that do not transfer data to the device). If the same command is scripted more than once, replies are sent in order they were scripted in, and the last one is repeated after that.
+
+* Unscripted SCSI commands are rejected with ILLEGAL REQUEST sense (INVALID COMMAND OPERATION CODE), just like real devices do.
+* If no ATA commands were scripted, this device pretends to be a plain SCSI device (i.e. ATA commands fail with `ATAError::NotSupported`); otherwise unscripted ATA commands are aborted (ABRT bit set in the error register).
+*/
+#[derive(Debug, Default)]
+pub struct MockDevice {
+	// (cdb, data out) → (sense, data)
+	scsi: Script<SCSIRequest, (Vec<u8>, Vec<u8>)>,
+	// (protocol, registers, data out) → (registers, data)
+	ata: Script<ATARequest<RegistersWrite>, (RegistersRead, Vec<u8>)>,
+	ata_ext: Script<ATARequest<RegistersWriteExt>, (RegistersReadExt, Vec<u8>)>,
+}
+
+type SCSIRequest = (Vec<u8>, Vec<u8>);
+type ATARequest<R> = (Protocol, R, Vec<u8>);
+
+// fixed format sense: ILLEGAL REQUEST, INVALID COMMAND OPERATION CODE
+const INVALID_OPCODE: [u8; 18] = [
+	0x70, 0, 0x05, 0, 0, 0, 0,
+	10, // additional sense length
+	0, 0, 0, 0,
+	0x20, 0x00, // asc, ascq
+	0, 0, 0, 0,
+];
+
+impl MockDevice {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Makes device reply to `cmd` (sent along with `data_out`) with given `sense` and `data`.
+	pub fn add_scsi(&mut self, cmd: &[u8], data_out: &[u8], sense: &[u8], data: &[u8]) {
+		self.scsi.add((cmd.to_vec(), data_out.to_vec()), Ok((sense.to_vec(), data.to_vec())));
+	}
+
+	/// Makes `cmd` (sent along with `data_out`) fail with given `failure`.
+	pub fn add_scsi_failure(&mut self, cmd: &[u8], data_out: &[u8], failure: Failure) {
+		self.scsi.add((cmd.to_vec(), data_out.to_vec()), Err(failure));
+	}
+
+	/// Makes device reply to `regs` (sent using `proto` along with `data_out`) with given registers and `data`.
+	pub fn add_ata(&mut self, proto: Protocol, regs: RegistersWrite, data_out: &[u8], reply: RegistersRead, data: &[u8]) {
+		self.ata.add((proto, regs, data_out.to_vec()), Ok((reply, data.to_vec())));
+	}
+
+	/// Makes `regs` (sent using `proto` along with `data_out`) fail with given `failure`.
+	pub fn add_ata_failure(&mut self, proto: Protocol, regs: RegistersWrite, data_out: &[u8], failure: Failure) {
+		self.ata.add((proto, regs, data_out.to_vec()), Err(failure));
+	}
+
+	/// Same as `add_ata`, but for 48-bit commands.
+	pub fn add_ata_ext(&mut self, proto: Protocol, regs: RegistersWriteExt, data_out: &[u8], reply: RegistersReadExt, data: &[u8]) {
+		self.ata_ext.add((proto, regs, data_out.to_vec()), Ok((reply, data.to_vec())));
+	}
+
+	/// Same as `add_ata_failure`, but for 48-bit commands.
+	pub fn add_ata_ext_failure(&mut self, proto: Protocol, regs: RegistersWriteExt, data_out: &[u8], failure: Failure) {
+		self.ata_ext.add((proto, regs, data_out.to_vec()), Err(failure));
+	}
+}
+
+impl SCSICommon for MockDevice {
+	fn do_cmd(&self, cmd: &[u8], dir: Direction, sense_len: usize, data_len: usize, data_out: &[u8], _timeout: Option<Duration>) -> Result<(Vec<u8>, Vec<u8>), scsi::Error> {
+		info!("mock SCSI cmd: dir={:?} cmd={:02x?}", dir, cmd);
+
+		let (mut sense, mut data) = match self.scsi.next(&(cmd.to_vec(), sent_data(dir, data_out).to_vec())) {
+			Some(reply) => reply?,
+			None => {
+				debug!("mock SCSI cmd is not scripted, rejecting");
+				(INVALID_OPCODE.to_vec(), vec![])
+			},
+		};
+
+		// do not return more than the caller asked for, just like SG_IO/CAM do
+		sense.truncate(sense_len);
+		match dir {
+			Direction::From | Direction::Both => data.truncate(data_len),
+			Direction::None | Direction::To => data.clear(),
+		}
+
+		Ok((sense, data))
+	}
+}
+
+impl ATATransport for MockDevice {
+	fn do_ata_cmd(&self, proto: Protocol, dir: Direction, regs: &RegistersWrite, data_out: &[u8], _timeout: Option<Duration>) -> Result<(RegistersRead, Vec<u8>), scsi::ATAError> {
+		if self.ata.is_empty() && self.ata_ext.is_empty() {
+			return Err(scsi::ATAError::NotSupported);
+		}
+		ata::transfer_len(dir, regs.sector_count as usize, data_out)?;
+
+		match self.ata.next(&(proto, regs.clone(), sent_data(dir, data_out).to_vec())) {
+			Some(reply) => Ok(reply?),
+			None => {
+				debug!("mock ATA cmd is not scripted, aborting");
+				Ok((RegistersRead {
+					error: 0x04, // ABRT
+					sector_count: 0,
+					sector: 0,
+					cyl_low: 0,
+					cyl_high: 0,
+					device: regs.device,
+					status: 0x41, // DRDY, ERR
+				}, vec![]))
+			},
+		}
+	}
+
+	fn do_ata_cmd_ext(&self, proto: Protocol, dir: Direction, regs: &RegistersWriteExt, data_out: &[u8], _timeout: Option<Duration>) -> Result<(RegistersReadExt, Vec<u8>), scsi::ATAError> {
+		if self.ata.is_empty() && self.ata_ext.is_empty() {
+			return Err(scsi::ATAError::NotSupported);
+		}
+		ata::transfer_len(dir, regs.sector_count as usize, data_out)?;
+
+		match self.ata_ext.next(&(proto, regs.clone(), sent_data(dir, data_out).to_vec())) {
+			Some(reply) => Ok(reply?),
+			None => {
+				debug!("mock ATA ext cmd is not scripted, aborting");
+				Ok((RegistersReadExt {
+					error: 0x04, // ABRT
+					sector_count: 0,
+					lba: 0,
+					device: regs.device,
+					status: 0x41, // DRDY, ERR
+				}, vec![]))
+			},
+		}
+	}
+}
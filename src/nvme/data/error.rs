@@ -0,0 +1,59 @@
+/*!
+Error Information log page (01h), as seen in NVM Express 1.4, 5.14.1.1.
+*/
+
+use byteorder::{ByteOrder, LittleEndian};
+
+/// Size of a single Error Information log entry
+pub const ERROR_ENTRY_LEN: usize = 64;
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serializable", derive(Serialize))]
+pub struct ErrorInfo {
+	/// Unique (and incrementing) identifier of the error
+	pub error_count: u64,
+	/// Submission queue the failed command was submitted to (0 is the admin queue)
+	pub sqid: u16,
+	/// Identifier of the failed command
+	pub cid: u16,
+	/// Status field of the completion queue entry (without the phase tag)
+	pub status: u16,
+	/// Byte (bits 7:0) and bit (bits 10:8) of the command parameter that caused the error; `None` if not applicable
+	pub param_error_location: Option<u16>,
+	/// First LBA that experienced the error condition
+	pub lba: u64,
+	pub nsid: u32,
+	/// Vendor-specific log page with additional information, if any
+	pub vendor_log_page: Option<u8>,
+	pub transport_type: u8,
+	pub command_specific: u64,
+	pub transport_specific: u16,
+}
+
+/// Parses Error Information log page, skipping empty entries; trailing bytes that do not constitute a complete entry are ignored.
+pub fn parse_error_log(data: &[u8]) -> Vec<ErrorInfo> {
+	data.chunks(ERROR_ENTRY_LEN)
+		.filter(|entry| entry.len() == ERROR_ENTRY_LEN)
+		.map(|entry| ErrorInfo {
+			error_count: LittleEndian::read_u64(&entry[0..8]),
+			sqid: LittleEndian::read_u16(&entry[8..10]),
+			cid: LittleEndian::read_u16(&entry[10..12]),
+			status: LittleEndian::read_u16(&entry[12..14]) >> 1,
+			param_error_location: match LittleEndian::read_u16(&entry[14..16]) {
+				0xffff => None,
+				x => Some(x),
+			},
+			lba: LittleEndian::read_u64(&entry[16..24]),
+			nsid: LittleEndian::read_u32(&entry[24..28]),
+			vendor_log_page: match entry[28] {
+				0 => None,
+				x => Some(x),
+			},
+			transport_type: entry[29],
+			command_specific: LittleEndian::read_u64(&entry[32..40]),
+			transport_specific: LittleEndian::read_u16(&entry[40..42]),
+		})
+		// error count of 0 marks an invalid (unused) entry
+		.filter(|entry| entry.error_count != 0)
+		.collect()
+}
@@ -0,0 +1,39 @@
+/*!
+Firmware Slot Information log page (03h), as seen in NVM Express 1.4, 5.14.1.3.
+*/
+
+use super::read_string;
+
+pub const FIRMWARE_LOG_LEN: usize = 512;
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serializable", derive(Serialize))]
+pub struct FirmwareLog {
+	/// Slot (1 to 7) the currently running firmware was loaded from
+	pub active_slot: u8,
+	/// Slot that is going to be activated at the next controller reset, if that's known
+	pub next_slot: Option<u8>,
+	/// Firmware revisions for slots 1 to 7; `None` for empty or unsupported slots
+	pub slots: Vec<Option<String>>,
+}
+
+/// Parses Firmware Slot Information log page; returns `None` if `data` is too short.
+pub fn parse_firmware_log(data: &[u8]) -> Option<FirmwareLog> {
+	if data.len() < FIRMWARE_LOG_LEN {
+		return None;
+	}
+
+	Some(FirmwareLog {
+		active_slot: data[0] & 0b111,
+		next_slot: match (data[0] >> 4) & 0b111 {
+			0 => None,
+			x => Some(x),
+		},
+		slots: data[8..64].chunks(8)
+			.map(|rev| match read_string(rev) {
+				ref s if s.is_empty() => None,
+				s => Some(s),
+			})
+			.collect(),
+	})
+}
@@ -0,0 +1,260 @@
+/*!
+Identify Controller (CNS 01h) and Identify Namespace (CNS 00h) data structures, as seen in NVM Express 1.4, 5.15.2.
+*/
+
+use byteorder::{ByteOrder, LittleEndian};
+
+use super::{read_string, read_u128_saturating};
+
+/// Both Identify Controller and Identify Namespace data structures are exactly this long
+pub const ID_LEN: usize = 4096;
+
+fn is_set(byte: u8, bit: usize) -> bool {
+	byte & (1<<bit) != 0
+}
+
+/// Optional Admin Command Support (OACS)
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serializable", derive(Serialize))]
+pub struct AdminCommands {
+	pub security: bool, // Security Send and Security Receive
+	pub format_nvm: bool,
+	pub firmware: bool, // Firmware Commit and Firmware Image Download
+	pub namespace_management: bool,
+	pub self_test: bool,
+	pub directives: bool,
+	pub nvme_mi: bool, // NVMe-MI Send and NVMe-MI Receive
+	pub virtualization_management: bool,
+	pub doorbell_buffer_config: bool,
+	pub get_lba_status: bool,
+}
+
+/// Optional NVM Command Support (ONCS)
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serializable", derive(Serialize))]
+pub struct NVMCommands {
+	pub compare: bool,
+	pub write_uncorrectable: bool,
+	pub dataset_management: bool,
+	pub write_zeroes: bool,
+	pub save_select: bool, // Save field in Set Features, Select field in Get Features
+	pub reservations: bool,
+	pub timestamp: bool,
+	pub verify: bool,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serializable", derive(Serialize))]
+pub struct Controller {
+	pub vendor_id: u16, // PCI vendor id
+	pub subsystem_vendor_id: u16,
+
+	pub serial: String,
+	pub model: String,
+	pub firmware: String,
+
+	pub ieee_oui: u32,
+	pub controller_id: u16,
+	/// (major, minor, tertiary); `None` for controllers that implement NVMe 1.1 or earlier
+	pub version: Option<(u16, u8, u8)>,
+	/// Maximum data transfer size, as a power of two, in units of the minimum memory page size; `None` means there's no limit
+	pub max_transfer_size: Option<u8>,
+
+	pub admin_commands: AdminCommands,
+	pub nvm_commands: NVMCommands,
+
+	pub firmware_slots: u8,
+	pub firmware_slot1_readonly: bool,
+
+	/// Whether SMART / Health Information log page can be requested for each namespace, not only for the controller as a whole
+	pub smart_per_namespace: bool,
+	pub error_log_entries: u16,
+	pub power_states: u16,
+
+	/// Warning composite temperature threshold, in Kelvins
+	pub warning_temp: Option<u16>,
+	/// Critical composite temperature threshold, in Kelvins
+	pub critical_temp: Option<u16>,
+
+	/// Total NVM capacity, in bytes; 0 if not reported
+	pub total_capacity: u64,
+	/// Unallocated NVM capacity, in bytes; 0 if not reported
+	pub unallocated_capacity: u64,
+
+	/// Extended self-test duration, in minutes
+	pub self_test_time: Option<u16>,
+
+	pub namespaces: u32,
+	pub volatile_write_cache: bool,
+
+	/// NVM Subsystem NVMe Qualified Name
+	pub subsystem_nqn: String,
+}
+
+/// Parses Identify Controller data structure; returns `None` if `data` is too short.
+pub fn parse_controller(data: &[u8]) -> Option<Controller> {
+	if data.len() < ID_LEN {
+		return None;
+	}
+
+	let version = LittleEndian::read_u32(&data[80..84]);
+	let oacs = LittleEndian::read_u16(&data[256..258]);
+	let oncs = LittleEndian::read_u16(&data[520..522]);
+	let oacs = |bit: u16| oacs & (1<<bit) != 0;
+	let oncs = |bit: u16| oncs & (1<<bit) != 0;
+
+	let temp = |offset: usize| match LittleEndian::read_u16(&data[offset .. offset+2]) {
+		0 => None,
+		t => Some(t),
+	};
+
+	Some(Controller {
+		vendor_id: LittleEndian::read_u16(&data[0..2]),
+		subsystem_vendor_id: LittleEndian::read_u16(&data[2..4]),
+
+		serial: read_string(&data[4..24]),
+		model: read_string(&data[24..64]),
+		firmware: read_string(&data[64..72]),
+
+		ieee_oui: LittleEndian::read_u24(&data[73..76]),
+		controller_id: LittleEndian::read_u16(&data[78..80]),
+		version: if version == 0 { None } else {
+			Some(((version >> 16) as u16, (version >> 8) as u8, version as u8))
+		},
+		max_transfer_size: if data[77] == 0 { None } else { Some(data[77]) },
+
+		admin_commands: AdminCommands {
+			security: oacs(0),
+			format_nvm: oacs(1),
+			firmware: oacs(2),
+			namespace_management: oacs(3),
+			self_test: oacs(4),
+			directives: oacs(5),
+			nvme_mi: oacs(6),
+			virtualization_management: oacs(7),
+			doorbell_buffer_config: oacs(8),
+			get_lba_status: oacs(9),
+		},
+		nvm_commands: NVMCommands {
+			compare: oncs(0),
+			write_uncorrectable: oncs(1),
+			dataset_management: oncs(2),
+			write_zeroes: oncs(3),
+			save_select: oncs(4),
+			reservations: oncs(5),
+			timestamp: oncs(6),
+			verify: oncs(7),
+		},
+
+		firmware_slots: (data[260] >> 1) & 0b111,
+		firmware_slot1_readonly: is_set(data[260], 0),
+
+		smart_per_namespace: is_set(data[261], 0),
+		// these two are 0's based values
+		error_log_entries: data[262] as u16 + 1,
+		power_states: data[263] as u16 + 1,
+
+		warning_temp: temp(266),
+		critical_temp: temp(268),
+
+		total_capacity: read_u128_saturating(&data[280..296]),
+		unallocated_capacity: read_u128_saturating(&data[296..312]),
+
+		self_test_time: if oacs(4) { Some(LittleEndian::read_u16(&data[316..318])) } else { None },
+
+		namespaces: LittleEndian::read_u32(&data[516..520]),
+		volatile_write_cache: is_set(data[525], 0),
+
+		// NUL-terminated
+		subsystem_nqn: read_string(&data[768..1024]),
+	})
+}
+
+/// LBA Format Data Structure
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serializable", derive(Serialize))]
+pub struct LBAFormat {
+	/// Number of metadata bytes per LBA
+	pub metadata_size: u16,
+	/// LBA data size, in bytes
+	pub data_size: u64,
+	/// 0 is the best performance, 3 is the worst
+	pub relative_performance: u8,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serializable", derive(Serialize))]
+pub struct Namespace {
+	/// Total size of the namespace, in logical blocks
+	pub size: u64,
+	/// Maximum number of logical blocks that may be allocated at any point in time
+	pub capacity: u64,
+	/// Number of logical blocks currently allocated
+	pub utilization: u64,
+	pub thin_provisioning: bool,
+
+	pub lba_formats: Vec<LBAFormat>,
+	/// Index of the LBA format (in `lba_formats`) the namespace was formatted with
+	pub lba_format: usize,
+
+	/// Size of the namespace, in bytes; 0 if not reported
+	pub nvm_capacity: u64,
+
+	/// Namespace Globally Unique Identifier, as a hex string
+	pub nguid: Option<String>,
+	/// IEEE Extended Unique Identifier, as a hex string
+	pub eui64: Option<String>,
+}
+
+impl Namespace {
+	/// Returns logical block size of the current LBA format, in bytes.
+	pub fn block_size(&self) -> Option<u64> {
+		self.lba_formats.get(self.lba_format).map(|f| f.data_size)
+	}
+}
+
+/// Returns hex string for the identifier, or `None` if it is all zeroes (i.e. not reported).
+fn read_id(data: &[u8]) -> Option<String> {
+	if data.iter().all(|&x| x == 0) {
+		return None;
+	}
+	Some(data.iter().map(|x| format!("{:02x}", x)).collect())
+}
+
+/// Parses Identify Namespace data structure; returns `None` if `data` is too short.
+pub fn parse_namespace(data: &[u8]) -> Option<Namespace> {
+	if data.len() < ID_LEN {
+		return None;
+	}
+
+	// NLBAF is 0's based
+	let lba_formats = (0 .. data[25] as usize + 1)
+		.map(|i| {
+			let format = LittleEndian::read_u32(&data[128 + 4*i .. 128 + 4*i + 4]);
+			LBAFormat {
+				metadata_size: format as u16,
+				// LBADS is a power of two, or 0 if this format is not supported
+				data_size: match (format >> 16) & 0xff {
+					0 => 0,
+					lbads => 1u64.checked_shl(lbads).unwrap_or(0),
+				},
+				relative_performance: ((format >> 24) & 0b11) as u8,
+			}
+		})
+		.collect();
+
+	Some(Namespace {
+		size: LittleEndian::read_u64(&data[0..8]),
+		capacity: LittleEndian::read_u64(&data[8..16]),
+		utilization: LittleEndian::read_u64(&data[16..24]),
+		thin_provisioning: is_set(data[24], 0),
+
+		lba_formats,
+		lba_format: (data[26] & 0x0f) as usize,
+
+		nvm_capacity: read_u128_saturating(&data[48..64]),
+
+		nguid: read_id(&data[104..120]),
+		eui64: read_id(&data[120..128]),
+	})
+}
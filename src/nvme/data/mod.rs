@@ -0,0 +1,27 @@
+pub mod id;
+pub mod smart;
+pub mod error;
+pub mod firmware;
+pub mod self_test;
+
+use byteorder::{ByteOrder, LittleEndian};
+
+/// Reads ASCII string padded with spaces (or, as it sometimes happens, NULs).
+pub(crate) fn read_string(data: &[u8]) -> String {
+	String::from_utf8_lossy(data)
+		.trim_matches(|c| c == ' ' || c == '\0')
+		.to_string()
+}
+
+/**
+Reads 128-bit little-endian counter.
+
+serde cannot serialize `u128`, and values that do not fit in 64 bits are not something we'll encounter any time soon, so this saturates at `u64::MAX` instead.
+*/
+pub(crate) fn read_u128_saturating(data: &[u8]) -> u64 {
+	if LittleEndian::read_u64(&data[8..16]) != 0 {
+		u64::MAX
+	} else {
+		LittleEndian::read_u64(&data[0..8])
+	}
+}
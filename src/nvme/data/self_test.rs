@@ -0,0 +1,128 @@
+/*!
+Device Self-test log page (06h), as seen in NVM Express 1.4, 5.14.1.6.
+*/
+
+use byteorder::{ByteOrder, LittleEndian};
+
+pub const SELF_TEST_LOG_LEN: usize = 564;
+const RESULT_LEN: usize = 28;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serializable", derive(Serialize))]
+pub enum SelfTestCode {
+	Short,
+	Extended,
+	VendorSpecific,
+	Reserved(u8),
+}
+
+impl SelfTestCode {
+	fn from(code: u8) -> Self {
+		use self::SelfTestCode::*;
+		match code {
+			0x1 => Short,
+			0x2 => Extended,
+			0xe => VendorSpecific,
+			x => Reserved(x),
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serializable", derive(Serialize))]
+pub enum SelfTestResult {
+	NoError,
+	/// Aborted by a Device Self-test command
+	Aborted,
+	AbortedByReset,
+	/// Aborted due to a removal of a namespace from the namespace inventory
+	AbortedByNamespaceRemoval,
+	AbortedByFormat,
+	/// Fatal error or unknown test error
+	Error,
+	/// Completed with a failed segment, and the segment that failed is not known
+	FailedUnknownSegment,
+	/// Completed with one or more failed segments, first one is reported in `SelfTest::segment`
+	FailedSegment,
+	AbortedUnknown,
+	AbortedBySanitize,
+	Reserved(u8),
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serializable", derive(Serialize))]
+pub struct SelfTest {
+	pub result: SelfTestResult,
+	pub code: SelfTestCode,
+	/// Number of the first segment that failed, if any
+	pub segment: Option<u8>,
+	/// Power-on hours at the time the test was completed or aborted
+	pub power_on_hours: u64,
+	/// Namespace in which the failure occurred, if any
+	pub nsid: Option<u32>,
+	pub failing_lba: Option<u64>,
+	/// (status code type, status code) of the failure, if any
+	pub status: Option<(u8, u8)>,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serializable", derive(Serialize))]
+pub struct SelfTestLog {
+	/// Test that is currently in progress, if any
+	pub current: Option<SelfTestCode>,
+	/// Completion percentage of the current test
+	pub current_completion: u8,
+	/// Results of the 20 most recent tests, newest first
+	pub results: Vec<SelfTest>,
+}
+
+/// Parses Device Self-test log page; returns `None` if `data` is too short.
+pub fn parse_self_test_log(data: &[u8]) -> Option<SelfTestLog> {
+	if data.len() < SELF_TEST_LOG_LEN {
+		return None;
+	}
+
+	let results = data[4 .. SELF_TEST_LOG_LEN].chunks(RESULT_LEN)
+		// result 0xf means that the entry is not used
+		.filter(|r| r[0] & 0xf != 0xf)
+		.map(|r| {
+			let valid = |bit: usize| r[2] & (1<<bit) != 0;
+
+			use self::SelfTestResult::*;
+			SelfTest {
+				result: match r[0] & 0xf {
+					0 => NoError,
+					1 => Aborted,
+					2 => AbortedByReset,
+					3 => AbortedByNamespaceRemoval,
+					4 => AbortedByFormat,
+					5 => Error,
+					6 => FailedUnknownSegment,
+					7 => FailedSegment,
+					8 => AbortedUnknown,
+					9 => AbortedBySanitize,
+					x => Reserved(x),
+				},
+				code: SelfTestCode::from(r[0] >> 4),
+				segment: match r[1] {
+					0 => None,
+					x => Some(x),
+				},
+				power_on_hours: LittleEndian::read_u64(&r[4..12]),
+				nsid: if valid(0) { Some(LittleEndian::read_u32(&r[12..16])) } else { None },
+				failing_lba: if valid(1) { Some(LittleEndian::read_u64(&r[16..24])) } else { None },
+				// status code is only valid along with status code type
+				status: if valid(2) && valid(3) { Some((r[24] & 0b111, r[25])) } else { None },
+			}
+		})
+		.collect();
+
+	Some(SelfTestLog {
+		current: match data[0] & 0xf {
+			0 => None,
+			x => Some(SelfTestCode::from(x)),
+		},
+		current_completion: data[1] & 0x7f,
+		results,
+	})
+}
@@ -0,0 +1,127 @@
+/*!
+SMART / Health Information log page (02h), as seen in NVM Express 1.4, 5.14.1.2.
+*/
+
+use byteorder::{ByteOrder, LittleEndian};
+
+use super::read_u128_saturating;
+
+pub const SMART_LOG_LEN: usize = 512;
+
+fn is_set(byte: u8, bit: usize) -> bool {
+	byte & (1<<bit) != 0
+}
+
+/// Critical Warning field; any of these being set means something is wrong with the controller
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serializable", derive(Serialize))]
+pub struct CriticalWarning {
+	/// Available spare capacity has fallen below the threshold
+	pub available_spare: bool,
+	/// Temperature is above an over temperature threshold or below an under temperature threshold
+	pub temperature: bool,
+	/// NVM subsystem reliability has been degraded due to significant media related errors or any internal error
+	pub reliability: bool,
+	/// Media has been placed in read only mode
+	pub read_only: bool,
+	/// Volatile memory backup device has failed
+	pub volatile_backup: bool,
+	/// Persistent Memory Region has become read-only or unreliable
+	pub persistent_memory: bool,
+}
+
+impl CriticalWarning {
+	pub fn any(&self) -> bool {
+		self.available_spare || self.temperature || self.reliability || self.read_only || self.volatile_backup || self.persistent_memory
+	}
+}
+
+/**
+SMART / Health Information log page.
+
+All temperatures are in Kelvins. 128-bit counters saturate at `u64::MAX`.
+*/
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serializable", derive(Serialize))]
+pub struct SmartLog {
+	pub critical_warning: CriticalWarning,
+	/// Composite temperature
+	pub temperature: u16,
+	/// Temperature sensors 1 to 8; `None` for sensors that are not implemented
+	pub temperature_sensors: Vec<Option<u16>>,
+
+	/// Normalized percentage of the remaining spare capacity
+	pub available_spare: u8,
+	/// When `available_spare` falls below this value, asynchronous event completion might occur
+	pub available_spare_threshold: u8,
+	/// Vendor-specific estimate of the percentage of life used, might exceed 100
+	pub percentage_used: u8,
+
+	/// Number of 512-byte data units read, in thousands (i.e. 1 is 512000 bytes), rounded up
+	pub data_units_read: u64,
+	/// Number of 512-byte data units written, in thousands (i.e. 1 is 512000 bytes), rounded up
+	pub data_units_written: u64,
+	pub host_read_commands: u64,
+	pub host_write_commands: u64,
+	/// Time the controller was busy with I/O commands, in minutes
+	pub controller_busy_time: u64,
+
+	pub power_cycles: u64,
+	pub power_on_hours: u64,
+	pub unsafe_shutdowns: u64,
+
+	/// Number of occurrences of unrecovered data integrity errors
+	pub media_errors: u64,
+	/// Number of Error Information log entries over the life of the controller
+	pub error_log_entries: u64,
+
+	/// Time spent above the warning composite temperature threshold, in minutes
+	pub warning_temp_time: u32,
+	/// Time spent above the critical composite temperature threshold, in minutes
+	pub critical_temp_time: u32,
+}
+
+/// Parses SMART / Health Information log page; returns `None` if `data` is too short.
+pub fn parse_smart_log(data: &[u8]) -> Option<SmartLog> {
+	if data.len() < SMART_LOG_LEN {
+		return None;
+	}
+
+	Some(SmartLog {
+		critical_warning: CriticalWarning {
+			available_spare: is_set(data[0], 0),
+			temperature: is_set(data[0], 1),
+			reliability: is_set(data[0], 2),
+			read_only: is_set(data[0], 3),
+			volatile_backup: is_set(data[0], 4),
+			persistent_memory: is_set(data[0], 5),
+		},
+		temperature: LittleEndian::read_u16(&data[1..3]),
+		temperature_sensors: (0..8)
+			.map(|i| match LittleEndian::read_u16(&data[200 + 2*i .. 200 + 2*i + 2]) {
+				0 => None,
+				t => Some(t),
+			})
+			.collect(),
+
+		available_spare: data[3],
+		available_spare_threshold: data[4],
+		percentage_used: data[5],
+
+		data_units_read: read_u128_saturating(&data[32..48]),
+		data_units_written: read_u128_saturating(&data[48..64]),
+		host_read_commands: read_u128_saturating(&data[64..80]),
+		host_write_commands: read_u128_saturating(&data[80..96]),
+		controller_busy_time: read_u128_saturating(&data[96..112]),
+
+		power_cycles: read_u128_saturating(&data[112..128]),
+		power_on_hours: read_u128_saturating(&data[128..144]),
+		unsafe_shutdowns: read_u128_saturating(&data[144..160]),
+
+		media_errors: read_u128_saturating(&data[160..176]),
+		error_log_entries: read_u128_saturating(&data[176..192]),
+
+		warning_temp_time: LittleEndian::read_u32(&data[192..196]),
+		critical_temp_time: LittleEndian::read_u32(&data[196..200]),
+	})
+}
@@ -0,0 +1,17 @@
+use std::io;
+use std::time::Duration;
+
+use Direction;
+use nvme::{NVMeDevice, AdminCmd, Error};
+
+// TODO NVME_PASSTHROUGH_CMD (see dev/nvme/nvme.h)
+impl NVMeDevice {
+	pub(crate) fn do_platform_cmd(&self, _dir: Direction, _cmd: &AdminCmd, _data_len: usize, _data_out: &[u8], _timeout: Option<Duration>) -> Result<(u32, Vec<u8>), Error> {
+		Err(io::Error::new(io::ErrorKind::Other, "NVMe is not supported on this platform yet"))?
+	}
+
+	/// Returns namespace id the device refers to, or `None` if this is a controller device (e.g. `/dev/nvme0`).
+	pub fn namespace_id(&self) -> Result<Option<u32>, io::Error> {
+		Ok(None)
+	}
+}
@@ -0,0 +1,107 @@
+use libc::ioctl;
+
+#[cfg(not(any(target_env = "musl")))]
+use libc::c_ulong;
+#[cfg(any(target_env = "musl"))]
+use libc::c_int;
+
+use std::os::unix::io::AsRawFd;
+use std::io;
+use std::time::Duration;
+
+use Direction;
+use nvme::{NVMeDevice, AdminCmd, Error};
+
+// see linux/nvme_ioctl.h
+
+// _IO('N', 0x40)
+#[cfg(not(any(target_env = "musl")))]
+const NVME_IOCTL_ID: c_ulong = 0x4e40;
+#[cfg(any(target_env = "musl"))]
+const NVME_IOCTL_ID: c_int = 0x4e40;
+
+// _IOWR('N', 0x41, struct nvme_admin_cmd)
+#[cfg(not(any(target_env = "musl")))]
+const NVME_IOCTL_ADMIN_CMD: c_ulong = 0xc0484e41;
+#[cfg(any(target_env = "musl"))]
+const NVME_IOCTL_ADMIN_CMD: c_int = 0xc0484e41u32 as c_int;
+
+#[repr(C)]
+#[derive(Debug, Default)]
+struct nvme_passthru_cmd {
+	opcode:	u8,
+	flags:	u8,
+	rsvd1:	u16,
+	nsid:	u32,
+	cdw2:	u32,
+	cdw3:	u32,
+	metadata:	u64,
+	addr:	u64,
+	metadata_len:	u32,
+	data_len:	u32,
+	cdw10:	u32,
+	cdw11:	u32,
+	cdw12:	u32,
+	cdw13:	u32,
+	cdw14:	u32,
+	cdw15:	u32,
+	timeout_ms:	u32,
+	result:	u32,
+}
+
+impl NVMeDevice {
+	pub(crate) fn do_platform_cmd(&self, dir: Direction, cmd: &AdminCmd, data_len: usize, data_out: &[u8], timeout: Option<Duration>) -> Result<(u32, Vec<u8>), Error> {
+		let mut data = match dir {
+			Direction::None => vec![],
+			Direction::From => vec![0; data_len],
+			// the kernel infers transfer direction from the opcode (see nvme_is_write()), so the buffer is passed the same way either way
+			Direction::To => data_out.to_vec(),
+			Direction::Both => return Err(Error::InvalidArgument("NVMe admin commands cannot transfer data in both directions")),
+		};
+
+		let mut hdr = nvme_passthru_cmd {
+			opcode:	cmd.opcode,
+			nsid:	cmd.nsid,
+			addr:	data.as_mut_ptr() as u64,
+			data_len:	data.len() as u32,
+			cdw10:	cmd.cdw10,
+			cdw11:	cmd.cdw11,
+			cdw12:	cmd.cdw12,
+			cdw13:	cmd.cdw13,
+			cdw14:	cmd.cdw14,
+			cdw15:	cmd.cdw15,
+			timeout_ms:	self.device.timeout_ms(timeout),
+			..Default::default()
+		};
+
+		// > On success, the NVMe status code is returned (0 on success, a positive value on NVMe errors)
+		let status = unsafe {
+			ioctl(self.device.file.as_raw_fd(), NVME_IOCTL_ADMIN_CMD, &mut hdr)
+		};
+		match status {
+			-1 => Err(io::Error::last_os_error())?,
+			0 => Ok((hdr.result, match dir {
+				// there's nothing to return but what was sent
+				Direction::To => vec![],
+				Direction::None | Direction::From | Direction::Both => data,
+			})),
+			status => Err(Error::Status(status as u16)),
+		}
+	}
+
+	/// Returns namespace id the device refers to, or `None` if this is a controller device (e.g. `/dev/nvme0`).
+	pub fn namespace_id(&self) -> Result<Option<u32>, io::Error> {
+		match unsafe { ioctl(self.device.file.as_raw_fd(), NVME_IOCTL_ID) } {
+			-1 => {
+				let err = io::Error::last_os_error();
+				// controller character devices do not implement this ioctl
+				if err.raw_os_error() == Some(::libc::ENOTTY) {
+					Ok(None)
+				} else {
+					Err(err)
+				}
+			},
+			nsid => Ok(Some(nsid as u32)),
+		}
+	}
+}
@@ -0,0 +1,237 @@
+/*!
+All things NVMe.
+
+* Use [`NVMeDevice`](struct.NVMeDevice.html) to send admin commands to the [`Device`](../device/index.html) (either controller, e.g. `/dev/nvme0`, or namespace, e.g. `/dev/nvme0n1`).
+* Use [`data` module](data/index.html) to parse various structures found in replies.
+* Import [`NVMeCommon`](trait.NVMeCommon.html) to do typical tasks without needing to compose commands and parse responses yourself.
+
+## Example
+
+```no_run
+use hdd::Device;
+use hdd::nvme::{NVMeDevice, NVMeCommon};
+
+let dev = NVMeDevice::new(Device::open("/dev/nvme0").unwrap());
+let id = dev.identify_controller().unwrap();
+println!("{} {}", id.model, id.firmware);
+```
+*/
+
+pub mod data;
+
+#[cfg(target_os = "linux")]
+mod linux;
+
+#[cfg(target_os = "freebsd")]
+mod freebsd;
+
+use std::io;
+use std::time::Duration;
+
+use Direction;
+use Device;
+
+use utils::hexdump_8;
+
+use self::data::{id, smart, error, firmware, self_test};
+
+quick_error! {
+	#[derive(Debug)]
+	pub enum Error {
+		IO(err: io::Error) {
+			from()
+			display("IO error: {}", err)
+			description(err.description())
+			cause(err)
+		}
+		/// Command completed with non-zero status field (see NVM Express 1.4, 4.6.1.2)
+		Status(status: u16) {
+			description("NVMe command failed")
+			display("NVMe command failed: status code type {:#x}, status code {:#04x}{}",
+				(status >> 8) & 0b111,
+				status & 0xff,
+				if status & (1<<14) != 0 { " (do not retry)" } else { "" },
+			)
+		}
+		InvalidArgument(what: &'static str) {
+			display("Invalid argument: {}", what)
+		}
+		InvalidData(what: &'static str) {
+			display("Unable to {}", what)
+		}
+	}
+}
+
+/// Self-test to start with [`NVMeCommon::start_self_test`](trait.NVMeCommon.html#method.start_self_test)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SelfTest {
+	Short = 0x1,
+	Extended = 0x2,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum AdminCommand {
+	GetLogPage = 0x02,
+	Identify = 0x06,
+	GetFeatures = 0x0a,
+	DeviceSelfTest = 0x14,
+}
+
+/// Submission queue entry fields that are relevant for admin commands (i.e. everything but data pointers)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct AdminCmd {
+	pub opcode: u8,
+	/// Namespace the command applies to; 0 if not used, 0xffffffff for all namespaces
+	pub nsid: u32,
+	pub cdw10: u32,
+	pub cdw11: u32,
+	pub cdw12: u32,
+	pub cdw13: u32,
+	pub cdw14: u32,
+	pub cdw15: u32,
+}
+
+#[derive(Debug)]
+pub struct NVMeDevice {
+	device: Device,
+}
+
+impl NVMeDevice {
+	pub fn new(device: Device) -> Self {
+		Self { device }
+	}
+
+	// thin wrapper against platform-specific implementation, mainly exists to provide consistent logging between platforms
+	/**
+	Executes admin command `cmd`, returning command-specific result (dword 0 of the completion queue entry) and the data.
+
+	`data_out` is the data sent to the device with `Direction::To` (and ignored otherwise); `data_len` is the size of the buffer that receives data from the device with `Direction::From`. Admin commands never transfer data in both directions, so `Direction::Both` results in `Error::InvalidArgument`.
+
+	`timeout` overrides device-wide timeout (see [`Device::set_timeout`](../device/struct.Device.html#method.set_timeout)) for this command only.
+	*/
+	pub fn do_cmd(&self, dir: Direction, cmd: &AdminCmd, data_len: usize, data_out: &[u8], timeout: Option<Duration>) -> Result<(u32, Vec<u8>), Error> {
+		info!("NVMe admin cmd: dir={:?} cmd={:x?}", dir, cmd);
+		if let Direction::To = dir {
+			debug!("NVMe data out: {}", hexdump_8(data_out));
+		}
+
+		// this one is implemented in `mod {linux,freebsd}`
+		let ret = Self::do_platform_cmd(self, dir, cmd, data_len, data_out, timeout);
+		match &ret {
+			Ok((result, data)) => {
+				debug!("NVMe result: {:#010x}", result);
+				debug!("NVMe data: {}", hexdump_8(data));
+			},
+			err => {
+				debug!("NVMe err: {:?}", err);
+			},
+		}
+		ret
+	}
+}
+
+pub trait NVMeCommon {
+	/// See [`NVMeDevice::do_cmd`](struct.NVMeDevice.html#method.do_cmd).
+	fn do_admin_cmd(&self, dir: Direction, cmd: &AdminCmd, data_len: usize, data_out: &[u8]) -> Result<(u32, Vec<u8>), Error>;
+
+	/// Issues IDENTIFY command with given Controller or Namespace Structure (CNS) value, returning raw data structure.
+	fn identify(&self, cns: u8, nsid: u32) -> Result<Vec<u8>, Error> {
+		info!("issuing IDENTIFY: cns={:#04x} nsid={}", cns, nsid);
+
+		let (_, data) = self.do_admin_cmd(Direction::From, &AdminCmd {
+			opcode: AdminCommand::Identify as u8,
+			nsid,
+			cdw10: cns as u32,
+			..Default::default()
+		}, id::ID_LEN, &[])?;
+		Ok(data)
+	}
+
+	fn identify_controller(&self) -> Result<id::Controller, Error> {
+		let data = self.identify(0x01, 0)?;
+		id::parse_controller(&data).ok_or(Error::InvalidData("parse identify controller data"))
+	}
+
+	fn identify_namespace(&self, nsid: u32) -> Result<id::Namespace, Error> {
+		let data = self.identify(0x00, nsid)?;
+		id::parse_namespace(&data).ok_or(Error::InvalidData("parse identify namespace data"))
+	}
+
+	/**
+	Issues GET LOG PAGE command, returning raw log page of `len` bytes (which should be a multiple of 4).
+
+	`nsid` is either a particular namespace, or `0xffffffff` for the controller as a whole (which is what most log pages expect).
+	*/
+	fn get_log_page(&self, log_id: u8, nsid: u32, len: usize) -> Result<Vec<u8>, Error> {
+		info!("issuing GET LOG PAGE: log={:#04x} nsid={:#x} len={}", log_id, nsid, len);
+
+		if len == 0 || len & 0b11 != 0 {
+			return Err(Error::InvalidArgument("log page length must be a non-zero multiple of 4"));
+		}
+		// Number of Dwords, 0's based
+		let numd = (len / 4 - 1) as u32;
+
+		let (_, data) = self.do_admin_cmd(Direction::From, &AdminCmd {
+			opcode: AdminCommand::GetLogPage as u8,
+			nsid,
+			cdw10: (numd & 0xffff) << 16 | log_id as u32,
+			cdw11: numd >> 16,
+			..Default::default()
+		}, len, &[])?;
+		Ok(data)
+	}
+
+	/// Reads SMART / Health Information log page for the controller as a whole.
+	fn smart_log(&self) -> Result<smart::SmartLog, Error> {
+		let data = self.get_log_page(0x02, 0xffff_ffff, smart::SMART_LOG_LEN)?;
+		smart::parse_smart_log(&data).ok_or(Error::InvalidData("parse SMART / Health Information log"))
+	}
+
+	/// Reads up to `entries` most recent Error Information log entries (see [`id::Controller::error_log_entries`](data/id/struct.Controller.html#structfield.error_log_entries)).
+	fn error_log(&self, entries: u16) -> Result<Vec<error::ErrorInfo>, Error> {
+		if entries == 0 {
+			return Err(Error::InvalidArgument("at least one error log entry must be requested"));
+		}
+		let data = self.get_log_page(0x01, 0xffff_ffff, entries as usize * error::ERROR_ENTRY_LEN)?;
+		Ok(error::parse_error_log(&data))
+	}
+
+	fn firmware_log(&self) -> Result<firmware::FirmwareLog, Error> {
+		let data = self.get_log_page(0x03, 0xffff_ffff, firmware::FIRMWARE_LOG_LEN)?;
+		firmware::parse_firmware_log(&data).ok_or(Error::InvalidData("parse Firmware Slot Information log"))
+	}
+
+	fn self_test_log(&self) -> Result<self_test::SelfTestLog, Error> {
+		let data = self.get_log_page(0x06, 0xffff_ffff, self_test::SELF_TEST_LOG_LEN)?;
+		self_test::parse_self_test_log(&data).ok_or(Error::InvalidData("parse Device Self-test log"))
+	}
+
+	/// Issues DEVICE SELF-TEST command with given Self-test Code (STC) for all namespaces.
+	fn device_self_test(&self, code: u8) -> Result<(), Error> {
+		info!("issuing DEVICE SELF-TEST: code={:#x}", code);
+
+		self.do_admin_cmd(Direction::None, &AdminCmd {
+			opcode: AdminCommand::DeviceSelfTest as u8,
+			nsid: 0xffff_ffff,
+			cdw10: code as u32,
+			..Default::default()
+		}, 0, &[])?;
+		Ok(())
+	}
+
+	/// Starts self-test in the background; use [`self_test_log`](#method.self_test_log) to track its progress.
+	fn start_self_test(&self, test: SelfTest) -> Result<(), Error> {
+		self.device_self_test(test as u8)
+	}
+
+	/// Aborts self-test that is currently in progress.
+	fn abort_self_test(&self) -> Result<(), Error> {
+		self.device_self_test(0xf)
+	}
+}
+
+impl NVMeCommon for NVMeDevice {
+	fn do_admin_cmd(&self, dir: Direction, cmd: &AdminCmd, data_len: usize, data_out: &[u8]) -> Result<(u32, Vec<u8>), Error> {
+		Self::do_cmd(self, dir, cmd, data_len, data_out, None)
+	}
+}
@@ -1,5 +1,5 @@
 /// Sense key descriptions, as seen in SPC-4, 4.5.6, table 43
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub enum SenseKey {
 	/// No Sense: indicates successful command execution, or might occur for a command that received CHECK CONDITION status because one of FILEMARK/EOM/ILI bits was set
 	Ok = 0,
@@ -3,19 +3,28 @@ use libc::{c_void, memcpy};
 use cam::{*, bindings::*};
 
 use Direction;
-use scsi::SCSIDevice;
+use scsi::{SCSIDevice, Outcome};
+use scsi::status::{Status, HostStatus, DriverStatus};
 
 use std::io;
+use std::cmp::max;
+use std::time::Duration;
 
 impl SCSIDevice {
-	/// Executes `cmd` and returns tuple of `(sense, data)`.
-	pub(crate) fn do_platform_cmd(&self, cmd: &[u8], dir: Direction, sense_len: usize, data_len: usize)-> Result<(Vec<u8>, Vec<u8>), io::Error> {
+	/// Executes `cmd` and returns its outcome.
+	pub(crate) fn do_platform_cmd(&self, cmd: &[u8], dir: Direction, sense_len: usize, data_len: usize, data_out: &[u8], timeout: Option<Duration>) -> Result<Outcome, io::Error> {
 		// might've used Vec::with_capacity(), but this requires rebuilding with Vec::from_raw_parts() later on to hint actual size of data in buffer vecs,
 		// and we're not expecting this function to be someone's bottleneck
 		let mut sense = vec![0; sense_len];
-		let mut data = vec![0; data_len];
-
-		let timeout = 10; // in seconds; TODO configurable
+		let mut data = match dir {
+			Direction::None | Direction::From => vec![0; data_len],
+			Direction::To => data_out.to_vec(),
+			Direction::Both => {
+				let mut data = data_out.to_vec();
+				data.resize(max(data_len, data_out.len()), 0);
+				data
+			},
+		};
 
 		let dev = &self.device.dev;
 
@@ -30,18 +39,17 @@ impl SCSIDevice {
 			csio.ccb_h.flags = {
 				use self::Direction::*;
 				match dir {
-					// TODO &[u8] arg → data → csio.data_ptr for Direction::{To,Both}
 					From => ccb_flags_CAM_DIR_IN,
-					To => unimplemented!(), //CAM_DIR_OUT,
-					Both => unimplemented!(), //CAM_DIR_BOTH,
+					To => ccb_flags_CAM_DIR_OUT,
+					Both => ccb_flags_CAM_DIR_BOTH,
 					None => ccb_flags_CAM_DIR_NONE,
 				}
 			};
 			csio.ccb_h.xflags = 0;
 			csio.ccb_h.retry_count = 1;
-			csio.ccb_h.timeout = timeout*1000;
+			csio.ccb_h.timeout = self.device.timeout_ms(timeout);
 			csio.data_ptr = data.as_mut_ptr();
-			csio.dxfer_len = data.capacity() as u32;
+			csio.dxfer_len = data.len() as u32;
 			csio.sense_len = sense.capacity() as u8;
 			csio.tag_action = MSG_SIMPLE_Q_TAG as u8;
 
@@ -61,8 +69,6 @@ impl SCSIDevice {
 			Err(error::from_status(dev, &ccb))?
 		}
 
-		// TODO ccb.csio.scsi_status
-
 		let sense_len =
 			if (ccb.get_status_flags() & cam_status_CAM_AUTOSNS_VALID) != 0 {
 				unsafe {
@@ -83,17 +89,26 @@ impl SCSIDevice {
 				0 // no valid sense, nothing to copy, sense has length 0
 			};
 
-		// TODO? return overrun flag
 		// XXX > u_int32_t resid; /* Transfer residual length: 2's comp */
 		// 2's comp uint?! WTF *!!*
 		// XXX resid, like sense_resid, is also always 0
-		let data_len = unsafe {
-			ccb.csio().dxfer_len - ccb.csio().resid
+		let resid = unsafe { ccb.csio().resid } as i32;
+		let data_len = match dir {
+			// nothing was read from the device, and there's no point in returning back what was sent
+			Direction::To => 0,
+			_ => unsafe { ccb.csio().dxfer_len } - max(resid, 0) as u32,
 		};
 
-		Ok((
-			sense[ .. sense_len as usize].to_vec(),
-			data[ .. data_len as usize].to_vec(),
-		))
+		Ok(Outcome {
+			sense: sense[ .. sense_len as usize].to_vec(),
+			data: data[ .. data_len as usize].to_vec(),
+			status: Status::from(unsafe { ccb.csio().scsi_status }),
+			// CAM reports transport errors through the ccb status, see above
+			host_status: HostStatus::Ok,
+			driver_status: DriverStatus::Ok,
+			resid: max(resid, 0) as usize,
+			overrun: resid < 0,
+			duration: None,
+		})
 	}
 }
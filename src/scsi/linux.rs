@@ -10,9 +10,11 @@ use std::os::unix::io::AsRawFd;
 use std::io;
 
 use Direction;
-use scsi::SCSIDevice;
+use scsi::{SCSIDevice, Outcome};
+use scsi::status::{Status, HostStatus, DriverStatus};
 
 use std::cmp::max;
+use std::time::Duration;
 
 // see scsi/sg.h
 
@@ -50,25 +52,33 @@ struct sg_io_hdr {
 }
 
 impl SCSIDevice {
-	pub(crate) fn do_platform_cmd(&self, cmd: &[u8], dir: Direction, sense_len: usize, data_len: usize) -> Result<(Vec<u8>, Vec<u8>), io::Error> {
+	pub(crate) fn do_platform_cmd(&self, cmd: &[u8], dir: Direction, sense_len: usize, data_len: usize, data_out: &[u8], timeout: Option<Duration>) -> Result<Outcome, io::Error> {
 		// might've used Vec::with_capacity(), but this requires rebuilding with Vec::from_raw_parts() later on to hint actual size of data in buffer vecs,
 		// and we're not expecting this function to be someone's bottleneck
 		let mut sense = vec![0; sense_len];
-		let mut data = vec![0; data_len];
+		let mut data = match dir {
+			Direction::None | Direction::From => vec![0; data_len],
+			Direction::To => data_out.to_vec(),
+			// SG_DXFER_TO_FROM_DEV: buffer is sent to the device first, and then it is overwritten with the reply
+			Direction::Both => {
+				let mut data = data_out.to_vec();
+				data.resize(max(data_len, data_out.len()), 0);
+				data
+			},
+		};
 
 		let hdr = sg_io_hdr {
 			interface_id:	'S' as c_int,
 
 			dxfer_direction: match dir {
 				// see scsi/sg.h, constants SG_DXFER_{NONE,{TO,FROM,TO_FROM}_DEV}
-				// TODO &[u8] arg → data → sg_io_hdr.dxferp for Direction::{To,Both}
 				Direction::None => -1,
-				Direction::To => unimplemented!(), //-2,
+				Direction::To => -2,
 				Direction::From => -3,
-				Direction::Both => unimplemented!(), //-4,
+				Direction::Both => -4,
 			},
 			dxferp:	data.as_mut_ptr() as *mut c_void,
-			dxfer_len:	data.capacity() as c_uint,
+			dxfer_len:	data.len() as c_uint,
 			resid:	0,
 
 			sbp:	sense.as_mut_ptr(),
@@ -82,7 +92,7 @@ impl SCSIDevice {
 			host_status:	0,
 			driver_status:	0,
 
-			timeout:	10000,	// TODO configurable
+			timeout:	self.device.timeout_ms(timeout),
 			duration:	0,
 
 			iovec_count:	0,
@@ -102,13 +112,23 @@ impl SCSIDevice {
 
 		// > In practice [resid] only reports underruns (i.e. positive number) as data overruns should never happen
 		// but I'd still not cast i32 to u32 blindly, just to be sure
-		// TODO? return overrun flag
 		// XXX sg_io set resid to 0 for SATA disks, and Hitachi SAS disks behind Adaptec also set this to 0 for things like LOG SENSE 0fh/00h—need more reading/testing
-		let data_len = hdr.dxfer_len - max(hdr.resid, 0) as u32;
+		let resid = max(hdr.resid, 0) as u32;
+		let data_len = match dir {
+			// nothing was read from the device, and there's no point in returning back what was sent
+			Direction::To => 0,
+			_ => hdr.dxfer_len - resid,
+		};
 
-		Ok((
-			sense[ .. hdr.sb_len_wr as usize].to_vec(),
-			data[ .. data_len as usize].to_vec(),
-		))
+		Ok(Outcome {
+			sense: sense[ .. hdr.sb_len_wr as usize].to_vec(),
+			data: data[ .. data_len as usize].to_vec(),
+			status: Status::from(hdr.status),
+			host_status: HostStatus::from(hdr.host_status),
+			driver_status: DriverStatus::from(hdr.driver_status),
+			resid: resid as usize,
+			overrun: hdr.resid < 0,
+			duration: Some(Duration::from_millis(hdr.duration as u64)),
+		})
 	}
 }
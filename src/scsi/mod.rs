@@ -9,6 +9,7 @@ All things SCSI.
 
 pub mod data;
 pub mod pages;
+pub mod status;
 
 #[cfg(target_os = "linux")]
 mod linux;
@@ -17,6 +18,8 @@ mod linux;
 mod freebsd;
 
 use std::io;
+use std::cell::Cell;
+use std::time::Duration;
 use ata;
 use byteorder::{ReadBytesExt, BigEndian};
 use self::data::sense;
@@ -49,6 +52,16 @@ quick_error! {
 		// this is for Sense::Fixed(FixedData::Invalid(_))
 		// pun definitely intented at this point
 		Nonsense {}
+		/// Command failed before reaching the device, or the device did not respond in time (e.g. `DID_TIME_OUT`, `DID_NO_CONNECT`)
+		Host(status: status::HostStatus) {
+			description("host adapter error")
+			display("host adapter error: {:?}", status)
+		}
+		/// Low-level driver failed to execute the command (e.g. `DRIVER_TIMEOUT`)
+		Driver(status: status::DriverStatus) {
+			description("driver error")
+			display("driver error: {:?}", status)
+		}
 	}
 }
 
@@ -76,30 +89,114 @@ quick_error! {
 		NotSupported {}
 		// no non-deferred sense is available, or there's no descriptors for ATA registers to be found
 		NoRegisters {}
+		/// Device replied to 48-bit command with fixed format sense, which cannot hold upper (HOB) bits of COUNT and LBA, and these bits are not zero
+		TruncatedRegisters {}
+		/// Transfer direction or length does not match the command (see [`ata::transfer_len`](../ata/fn.transfer_len.html))
+		InvalidTransfer {}
+	}
+}
+
+/// Result of a command that was sent to the device, along with everything the OS has to say about it.
+#[derive(Debug)]
+pub struct Outcome {
+	pub sense: Vec<u8>,
+	pub data: Vec<u8>,
+	/// Status byte returned by the device; `CheckCondition` means there's `sense` to look at
+	pub status: status::Status,
+	/// Always `HostStatus::Ok` on platforms that report these errors as `io::Error`s instead (e.g. FreeBSD)
+	pub host_status: status::HostStatus,
+	/// Always `DriverStatus::Ok` on platforms that report these errors as `io::Error`s instead (e.g. FreeBSD)
+	pub driver_status: status::DriverStatus,
+	/// Number of bytes that were expected but not transferred (underrun)
+	pub resid: usize,
+	/// Whether device tried to transfer more data than requested
+	pub overrun: bool,
+	/// Time it took to execute the command, if reported by the OS
+	pub duration: Option<Duration>,
+}
+
+impl Outcome {
+	/// Returns `Err()` if command did not make it to the device or back, as indicated by the host adapter or the driver.
+	pub fn check(&self) -> Result<(), Error> {
+		if self.host_status != status::HostStatus::Ok {
+			return Err(Error::Host(self.host_status));
+		}
+		if self.driver_status.is_error() {
+			return Err(Error::Driver(self.driver_status));
+		}
+		Ok(())
 	}
 }
 
 #[derive(Debug)]
 pub struct SCSIDevice {
 	device: Device,
+	ata_pass_through: Cell<Option<ATAPassThrough>>,
 }
 
 impl SCSIDevice {
 	pub fn new(device: Device) -> Self {
-		Self { device }
+		Self {
+			device,
+			ata_pass_through: Cell::new(None),
+		}
+	}
+
+	/**
+	Returns ATA PASS-THROUGH command that is known to work with this device, if any.
+
+	When sending ATA commands (see [`ATATransport`](../ata/trait.ATATransport.html)), `SCSIDevice` tries ATA PASS-THROUGH (16) first, and falls back to ATA PASS-THROUGH (12) if the former is not supported; whichever one succeeds first is then used for all the subsequent commands.
+	*/
+	pub fn ata_pass_through(&self) -> Option<ATAPassThrough> {
+		self.ata_pass_through.get()
+	}
+
+	pub(crate) fn set_ata_pass_through(&self, pt: ATAPassThrough) {
+		self.ata_pass_through.set(Some(pt));
+	}
+
+	/// Sets the time limit for every command sent to this device; see [`Device::set_timeout`](../device/struct.Device.html#method.set_timeout).
+	pub fn set_timeout(&mut self, timeout: Duration) {
+		self.device.set_timeout(timeout);
+	}
+
+	pub fn timeout(&self) -> Duration {
+		self.device.timeout()
 	}
 
 	// thin wrapper against platform-specific implementation, mainly exists to provide consistent logging between platforms
-	/// Executes `cmd` and returns tuple of `(sense, data)`.
-	pub fn do_cmd(&self, cmd: &[u8], dir: Direction, sense_len: usize, data_len: usize) -> Result<(Vec<u8>, Vec<u8>), io::Error> {
-		info!("SCSI cmd: dir={:?} cmd={:02x?}", dir, cmd);
+	/**
+	Executes `cmd` and returns its [`Outcome`](struct.Outcome.html).
+
+	Note that this function does not check whether the command actually succeeded; see [`Outcome::check`](struct.Outcome.html#method.check).
+
+	`data_out` is the parameter list sent to the device with `Direction::To` and `Direction::Both` (and ignored otherwise); `data_len` is the size of the buffer that receives data from the device, and is ignored for `Direction::To`.
+
+	For `Direction::Both`, the same buffer is used for both transfers, so it is never shorter than `data_out`.
+
+	`timeout` overrides device-wide timeout (see [`set_timeout`](#method.set_timeout)) for this command only, e.g. for commands that are known to take a while.
+	*/
+	pub fn do_cmd(&self, cmd: &[u8], dir: Direction, sense_len: usize, data_len: usize, data_out: &[u8], timeout: Option<Duration>) -> Result<Outcome, io::Error> {
+		info!("SCSI cmd: dir={:?} cmd={:02x?} timeout={:?}", dir, cmd, timeout.unwrap_or(self.timeout()));
+		match dir {
+			Direction::To | Direction::Both => debug!("SCSI data out: {}", hexdump_8(data_out)),
+			Direction::None | Direction::From => (),
+		}
 
 		// this one is implemented in `mod {linux,freebsd}`
-		let ret = Self::do_platform_cmd(self, cmd, dir, sense_len, data_len);
+		let ret = Self::do_platform_cmd(self, cmd, dir, sense_len, data_len, data_out, timeout);
 		match &ret {
-			Ok((sense, data)) => {
-				debug!("SCSI autosense: {}", hexdump_8(sense));
-				debug!("SCSI data: {}", hexdump_8(data));
+			Ok(outcome) => {
+				debug!("SCSI status: {:?} host={:?} driver={:?} resid={} overrun={} duration={:?}",
+					outcome.status,
+					outcome.host_status,
+					outcome.driver_status,
+					outcome.resid,
+					outcome.overrun,
+					outcome.duration,
+				);
+				debug!("SCSI autosense: {}", hexdump_8(&outcome.sense));
+				debug!("SCSI data: {}", hexdump_8(&outcome.data));
 			},
 			err => {
 				debug!("SCSI err: {:?}", err);
@@ -129,9 +226,16 @@ pub enum DefectList {
 }
 
 // TODO look for non-empty autosense and turn it into errors where appropriate
-pub trait SCSICommon: Sized {
+pub trait SCSICommon {
 	// XXX DRY
-	fn do_cmd(&self, cmd: &[u8], dir: Direction, sense_len: usize, data_len: usize) -> Result<(Vec<u8>, Vec<u8>), io::Error>;
+	/**
+	Executes `cmd` and returns tuple of `(sense, data)`.
+
+	Unlike [`SCSIDevice::do_cmd`](struct.SCSIDevice.html#method.do_cmd), this returns `Err()` if host adapter or driver failed to execute the command.
+
+	Just like with `SCSIDevice::do_cmd`, `timeout` overrides device-wide timeout for this command only; devices that have no notion of timeouts (e.g. [`MockDevice`](../mock/struct.MockDevice.html)) ignore it.
+	*/
+	fn do_cmd(&self, cmd: &[u8], dir: Direction, sense_len: usize, data_len: usize, data_out: &[u8], timeout: Option<Duration>) -> Result<(Vec<u8>, Vec<u8>), Error>;
 
 	fn scsi_inquiry(&self, vital: bool, code: u8) -> Result<(Vec<u8>, Vec<u8>), Error> {
 		info!("issuing INQUIRY: code={:?} vital={:?}", code, vital);
@@ -148,7 +252,7 @@ pub trait SCSICommon: Sized {
 			0, // control (XXX what's that?!)
 		];
 
-		Ok(self.do_cmd(&cmd, Direction::From, 32, alloc)?)
+		self.do_cmd(&cmd, Direction::From, 32, alloc, &[], None)
 	}
 
 	/// returns tuple of (sense, logical block address, block length in bytes)
@@ -174,7 +278,7 @@ pub trait SCSICommon: Sized {
 			0, // control (XXX what's that?!)
 		];
 
-		let (sense, data) = self.do_cmd(&cmd, Direction::From, 32, 8)?;
+		let (sense, data) = self.do_cmd(&cmd, Direction::From, 32, 8, &[], None)?;
 
 		Ok((
 			sense,
@@ -266,116 +370,241 @@ pub trait SCSICommon: Sized {
 			0, // control (XXX what's that?!)
 		];
 
-		Ok(self.do_cmd(&cmd, Direction::From, 32, alloc)?)
+		self.do_cmd(&cmd, Direction::From, 32, alloc, &[], None)
 	}
 
-	fn ata_pass_through_16(&self, dir: Direction, regs: &ata::RegistersWrite) -> Result<(ata::RegistersRead, Vec<u8>), ATAError> {
-		info!("issuing ATA PASS-THROUGH (16): dir={:?} regs={:?}", dir, regs);
+	/**
+	Issues ATA command using ATA PASS-THROUGH (16).
 
-		// see T10/04-262r8a ATA Command Pass-Through, 3.2.3
-		let extend = 0; // TODO
-		let protocol = match dir {
-			Direction::None => 3, // Non-data
-			Direction::From => 4, // PIO Data-In
-			Direction::To => unimplemented!(), //5, // PIO Data-Out
-			_ => unimplemented!(),
-		};
-		let multiple_count = 0; // TODO
-		let ata_cmd: [u8; 16] = [
-			0x85, // opcode: ATA PASS-THROUGH (16)
-			(multiple_count << 5) + (protocol << 1) + extend,
-			// 0b00: wait up to 2^(OFF_LINE+1)-2 seconds for valid ATA status register
-			// 0b1: CK_COND, return ATA register info in the sense data
-			// 0b0: reserved
-			// 0b1: T_DIR; transfer from ATA device
-			// 0b1: BYT_BLOK; T_LENGTH is in blocks, not in bytes
-			// 0b01: T_LENGTH itself
-			0b0010_1101,
-			0, regs.features,
-			0, regs.sector_count,
-			0, regs.sector,
-			0, regs.cyl_low,
-			0, regs.cyl_high,
-			regs.device,
-			regs.command,
-			0, // control (XXX what's that?!)
-		];
+	Transfer length (in 512-byte blocks) is taken from the sector count register, which should not be 0; for `Direction::To`, `data_out` should be exactly that long (see [`ata::transfer_len`](../ata/fn.transfer_len.html)). `proto` is ignored for non-data commands.
 
-		let (sense, data) = self.do_cmd(&ata_cmd, Direction::From, 32, 512)?;
+	`timeout` overrides device-wide timeout for this command only (see [`do_cmd`](#tymethod.do_cmd)).
+	*/
+	fn ata_pass_through_16(&self, proto: ata::Protocol, dir: Direction, regs: &ata::RegistersWrite, data_out: &[u8], timeout: Option<Duration>) -> Result<(ata::RegistersRead, Vec<u8>), ATAError> {
+		info!("issuing ATA PASS-THROUGH (16): proto={:?} dir={:?} regs={:?}", proto, dir, regs);
 
-		let sense = match sense::parse(&sense) {
-			Some((true, sense)) => sense,
-			Some((false, _)) | None => {
-				// no (current) sense
-				return Err(ATAError::NoRegisters);
-			},
-		};
+		ata_pass_through(self, dir, regs.sector_count as usize, data_out, timeout, || ata_pass_through_16_cdb(proto, dir, false, &regs.into()))
+			.map(|(regs, data)| (regs.into(), data))
+	}
 
-		let descriptors = match sense {
-			// current sense in the descriptor format
-			sense::Sense::Descriptor(sense::DescriptorData {
-				descriptors,
-				// Recovered Error / ATA PASS THROUGH INFORMATION AVAILABLE
-				key: 0x01, asc: 0x00, ascq: 0x1D,
-				..
-			}) => {
-				descriptors
-			},
+	/// Same as `ata_pass_through_16`, but for 48-bit commands (i.e. with EXTEND bit set).
+	fn ata_pass_through_16_ext(&self, proto: ata::Protocol, dir: Direction, regs: &ata::RegistersWriteExt, data_out: &[u8], timeout: Option<Duration>) -> Result<(ata::RegistersReadExt, Vec<u8>), ATAError> {
+		info!("issuing ATA PASS-THROUGH (16) with EXTEND bit: proto={:?} dir={:?} regs={:?}", proto, dir, regs);
 
-			sense::Sense::Descriptor(sense::DescriptorData {
-				descriptors,
-				// some devices/drivers return (Ok, 0, 0) as a sense;
-				// will validate its contents below
-				key: 0x00, asc: 0x00, ascq: 0x00,
-				..
-			}) => {
-				descriptors
-			},
+		ata_pass_through(self, dir, regs.sector_count as usize, data_out, timeout, || ata_pass_through_16_cdb(proto, dir, true, regs))
+	}
 
-			sense::Sense::Fixed(sense::FixedData::Valid {
-				// Illegal Request / INVALID COMMAND OPERATION CODE
-				key: 0x05, asc: 0x20, ascq: 0x00, ..
-			}) => {
-				return Err(ATAError::NotSupported);
-			},
+	/**
+	Same as `ata_pass_through_16`, but uses 12-byte CDB, which is the only one some USB bridges and older SATLs understand.
 
-			// unexpected sense
-			sense => return Err(Error::from_sense(&sense))?,
-		};
+	Note that the very same opcode (A1h) means BLANK for MMC devices (e.g. CD-RW drives), so make sure you're not sending this to one of those.
+	*/
+	fn ata_pass_through_12(&self, proto: ata::Protocol, dir: Direction, regs: &ata::RegistersWrite, data_out: &[u8], timeout: Option<Duration>) -> Result<(ata::RegistersRead, Vec<u8>), ATAError> {
+		info!("issuing ATA PASS-THROUGH (12): proto={:?} dir={:?} regs={:?}", proto, dir, regs);
+
+		ata_pass_through(self, dir, regs.sector_count as usize, data_out, timeout, || ata_pass_through_12_cdb(proto, dir, regs))
+			.map(|(regs, data)| (regs.into(), data))
+	}
+}
+
+/// Composes ATA PASS-THROUGH (12) CDB.
+fn ata_pass_through_12_cdb(proto: ata::Protocol, dir: Direction, regs: &ata::RegistersWrite) -> [u8; 12] {
+	// see T10/04-262r8a ATA Command Pass-Through, 3.2.2
+	let (protocol, flags) = ata_pass_through_flags(proto, dir);
+	let multiple_count = 0; // TODO
+	[
+		0xa1, // opcode: ATA PASS-THROUGH (12)
+		(multiple_count << 5) + (protocol << 1),
+		flags,
+		regs.features,
+		regs.sector_count,
+		regs.sector,
+		regs.cyl_low,
+		regs.cyl_high,
+		regs.device,
+		regs.command,
+		0, // reserved
+		0, // control
+	]
+}
+
+/**
+Sends ATA PASS-THROUGH CDB (either 12- or 16-byte one, composed by `cdb`), transferring `blocks` 512-byte blocks from the device or `data_out` to the device.
+
+Transfer is validated before the CDB is even composed, so `cdb` never sees requests that make no sense (e.g. `Direction::Both`).
+*/
+fn ata_pass_through<D, F, C>(dev: &D, dir: Direction, blocks: usize, data_out: &[u8], timeout: Option<Duration>, cdb: F) -> Result<(ata::RegistersReadExt, Vec<u8>), ATAError>
+where D: SCSICommon + ?Sized, F: FnOnce() -> C, C: AsRef<[u8]> {
+	// data_len is ignored for Direction::To, and data_out is ignored for everything but Direction::To
+	let data_len = ata::transfer_len(dir, blocks, data_out)?;
+	let (sense, data) = dev.do_cmd(cdb().as_ref(), dir, 32, data_len, data_out, timeout)?;
+	parse_ata_pass_through_reply(&sense, data)
+}
+
+/// Composes ATA PASS-THROUGH (16) CDB; with `extend` unset, only lower 8 bits of each register are used.
+fn ata_pass_through_16_cdb(proto: ata::Protocol, dir: Direction, extend: bool, regs: &ata::RegistersWriteExt) -> [u8; 16] {
+	// see T10/04-262r8a ATA Command Pass-Through, 3.2.3
+	let (protocol, flags) = ata_pass_through_flags(proto, dir);
+	let multiple_count = 0; // TODO
+	// previous register contents are ignored if EXTEND bit is not set, but let's not send garbage anyway
+	let hob = |x: u8| if extend { x } else { 0 };
+	[
+		0x85, // opcode: ATA PASS-THROUGH (16)
+		(multiple_count << 5) + (protocol << 1) + extend as u8,
+		flags,
+		hob((regs.features >> 8) as u8), regs.features as u8,
+		hob((regs.sector_count >> 8) as u8), regs.sector_count as u8,
+		hob((regs.lba >> 24) as u8), regs.lba as u8,
+		hob((regs.lba >> 32) as u8), (regs.lba >> 8) as u8,
+		hob((regs.lba >> 40) as u8), (regs.lba >> 16) as u8,
+		regs.device,
+		regs.command,
+		0, // control (XXX what's that?!)
+	]
+}
+
+/// Returns PROTOCOL field and the byte that follows it in ATA PASS-THROUGH CDBs (both 12- and 16-byte ones).
+fn ata_pass_through_flags(proto: ata::Protocol, dir: Direction) -> (u8, u8) {
+	use ata::Protocol::*;
+	let protocol = match (proto, dir) {
+		(_, Direction::None) => 3, // Non-data
+		(PIO, Direction::From) => 4, // PIO Data-In
+		(PIO, Direction::To) => 5, // PIO Data-Out
+		(DMA, Direction::From) | (DMA, Direction::To) => 6, // DMA
+		(UDMA, Direction::From) => 10, // UDMA Data In
+		(UDMA, Direction::To) => 11, // UDMA Data Out
+		// rejected by ata_pass_through() before the CDB is composed
+		(_, Direction::Both) => unreachable!(),
+	};
+	// 0b00: wait up to 2^(OFF_LINE+1)-2 seconds for valid ATA status register
+	// 0b1: CK_COND, return ATA register info in the sense data
+	// 0b0: reserved
+	// 0b?: T_DIR; whether transfer is from (1) or to (0) ATA device
+	// 0b1: BYT_BLOK; T_LENGTH is in blocks, not in bytes
+	// 0b??: T_LENGTH itself: 0b00 for no data, 0b10 for sector count register
+	let flags = match dir {
+		Direction::None => 0b0010_0000,
+		Direction::From => 0b0010_1110,
+		Direction::To => 0b0010_0110,
+		// see above
+		Direction::Both => unreachable!(),
+	};
+	(protocol, flags)
+}
+
+/**
+Extracts ATA registers from the sense that device returned in response to ATA PASS-THROUGH command.
 
-		for desc in descriptors {
-			if desc.code != 0x09 { continue; }
-			if desc.data.len() != 12 { continue; }
+Previous (HOB) register contents are only available in descriptor format sense, and only if the command had EXTEND bit set; otherwise these bits are zero.
+*/
+fn parse_ata_pass_through_reply(sense: &Vec<u8>, data: Vec<u8>) -> Result<(ata::RegistersReadExt, Vec<u8>), ATAError> {
+	let sense = match sense::parse(sense) {
+		Some((true, sense)) => sense,
+		Some((false, _)) | None => {
+			// no (current) sense
+			return Err(ATAError::NoRegisters);
+		},
+	};
 
-			let d = desc.data;
+	match sense.kcq() {
+		// Illegal Request / INVALID COMMAND OPERATION CODE
+		Some((0x05, 0x20, 0x00)) => return Err(ATAError::NotSupported),
+		// Recovered Error / ATA PASS THROUGH INFORMATION AVAILABLE
+		Some((0x01, 0x00, 0x1D)) => (),
+		// some devices/drivers return (Ok, 0, 0) as a sense;
+		// will validate its contents below
+		Some((0x00, 0x00, 0x00)) => (),
+		// unexpected sense
+		_ => return Err(Error::from_sense(&sense))?,
+	}
 
-			// TODO? EXTEND bit, ATA PASS-THROUGH 12 vs 16
-			return Ok((ata::RegistersRead {
-				error: d[1],
+	match sense {
+		sense::Sense::Descriptor(sense::DescriptorData { descriptors, .. }) => {
+			for desc in descriptors {
+				if desc.code != 0x09 { continue; }
+				if desc.data.len() != 12 { continue; }
 
-				sector_count: d[3],
+				let d = desc.data;
 
-				sector: d[5],
-				cyl_low: d[7],
-				cyl_high: d[9],
-				device: d[10],
+				// d[0] is EXTEND bit, and if it is not set, previous register contents are not valid
+				let hob = |x: u8| if d[0] & 1 != 0 { x as u64 } else { 0 };
+				return Ok((ata::RegistersReadExt {
+					error: d[1],
 
-				status: d[11],
+					sector_count: (hob(d[2]) << 8 | d[3] as u64) as u16,
+
+					lba: d[5] as u64
+						| (d[7] as u64) << 8
+						| (d[9] as u64) << 16
+						| hob(d[4]) << 24
+						| hob(d[6]) << 32
+						| hob(d[8]) << 40,
+					device: d[10],
+
+					status: d[11],
+				}, data))
+			}
+
+			Err(ATAError::NoRegisters)
+		},
+
+		// see T10/04-262r8a ATA Command Pass-Through, 3.4.5:
+		// INFORMATION field is ERROR, STATUS, DEVICE and COUNT (7:0),
+		// COMMAND-SPECIFIC INFORMATION field is [EXTEND, upper bits of COUNT and LBA are non-zero, LOG INDEX], followed by LBA (23:0)
+		// (unlike descriptors, there's nothing here to validate, so don't trust (Ok, 0, 0) senses)
+		sense::Sense::Fixed(sense::FixedData::Valid { info, cmd_info, key: 0x01, asc: 0x00, ascq: 0x1D, .. }) => {
+			// upper COUNT/LBA bits are not available here, the best we can get is whether they are non-zero;
+			// for 48-bit commands that's not good enough, as returned registers are only valid if these bits are zero
+			let extend = cmd_info[0] & 0b1000_0000 != 0;
+			let upper_count = cmd_info[0] & 0b0100_0000 != 0;
+			let upper_lba = cmd_info[0] & 0b0010_0000 != 0;
+			if extend && (upper_count || upper_lba) {
+				return Err(ATAError::TruncatedRegisters);
+			}
+
+			Ok((ata::RegistersReadExt {
+				error: info[0],
+
+				sector_count: info[3] as u16,
+
+				lba: cmd_info[1] as u64
+					| (cmd_info[2] as u64) << 8
+					| (cmd_info[3] as u64) << 16,
+				device: info[2],
+
+				status: info[1],
 			}, data))
-		}
+		},
 
-		return Err(ATAError::NoRegisters);
+		sense::Sense::Fixed(_) => Err(ATAError::NoRegisters),
 	}
 }
 
+/// Flavour of ATA PASS-THROUGH command, see [`SCSIDevice::ata_pass_through`](struct.SCSIDevice.html#method.ata_pass_through)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ATAPassThrough {
+	/// ATA PASS-THROUGH (12), opcode A1h
+	CDB12,
+	/// ATA PASS-THROUGH (16), opcode 85h
+	CDB16,
+}
+
 impl SCSICommon for SCSIDevice {
 	// XXX DRY
-	fn do_cmd(&self, cmd: &[u8], dir: Direction, sense_len: usize, data_len: usize) -> Result<(Vec<u8>, Vec<u8>), io::Error> {
-		Self::do_cmd(self, cmd, dir, sense_len, data_len)
+	fn do_cmd(&self, cmd: &[u8], dir: Direction, sense_len: usize, data_len: usize, data_out: &[u8], timeout: Option<Duration>) -> Result<(Vec<u8>, Vec<u8>), Error> {
+		let outcome = Self::do_cmd(self, cmd, dir, sense_len, data_len, data_out, timeout)?;
+		outcome.check()?;
+		Ok((outcome.sense, outcome.data))
+	}
+}
+
+impl<T: SCSICommon + ?Sized> SCSICommon for Box<T> {
+	fn do_cmd(&self, cmd: &[u8], dir: Direction, sense_len: usize, data_len: usize, data_out: &[u8], timeout: Option<Duration>) -> Result<(Vec<u8>, Vec<u8>), Error> {
+		(**self).do_cmd(cmd, dir, sense_len, data_len, data_out, timeout)
 	}
 }
 
-fn read_defect_data<D: SCSICommon, C>(
+fn read_defect_data<D: SCSICommon + ?Sized, C>(
 	log_detail: usize,
 	dev: &D,
 	list: DefectList,
@@ -400,7 +629,7 @@ where C: ::std::ops::Div<Output = C> + ::std::convert::From<u8> + ::std::fmt::Di
 	let glist = if glist { 1 } else { 0 };
 
 	let (cmd, alloc) = cmd(plist, glist, format);
-	let (sense, data) = dev.do_cmd(&cmd, Direction::From, 32, alloc)?;
+	let (sense, data) = dev.do_cmd(&cmd, Direction::From, 32, alloc, &[], None)?;
 
 	if sense.len() > 0 {
 		// only current senses are expected here
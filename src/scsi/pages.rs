@@ -22,7 +22,7 @@ if pages.contains(0x03) {
 */
 
 use scsi;
-use scsi::{SCSIDevice, SCSICommon};
+use scsi::SCSICommon;
 use scsi::data::log_page;
 
 extern crate byteorder;
@@ -149,9 +149,9 @@ pub struct SCSIPages<'a, T: SCSICommon + 'a> {
 }
 
 // TODO non-empty autosense errors
-impl<'a> SCSIPages<'a, SCSIDevice> {
+impl<'a, T: SCSICommon> SCSIPages<'a, T> {
 	// TODO document error type
-	pub fn new(device: &'a SCSIDevice) -> Result<Self, Error> {
+	pub fn new(device: &'a T) -> Result<Self, Error> {
 		// no public method here can work without list of supported pages, so cache it right away or Err() out
 		info!("querying list of supported page");
 		let supported_pages = Self::get_page_unchecked(device, 0x00)?.data.to_vec();
@@ -0,0 +1,211 @@
+/*!
+Status codes that accompany every executed SCSI command.
+
+* [`Status`](enum.Status.html) is the status byte returned by the device server itself (SAM-5, 5.3).
+* [`HostStatus`](enum.HostStatus.html) and [`DriverStatus`](enum.DriverStatus.html) are Linux-specific (see `scsi/scsi.h` and `scsi/sg.h`), and describe what happened with the command on its way to and from the device.
+*/
+
+/// SCSI status byte, as seen in SAM-5, 5.3.1, table 38
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+	Good,
+	/// Sense data is available and should be examined
+	CheckCondition,
+	ConditionMet,
+	Busy,
+	ReservationConflict,
+	TaskSetFull,
+	ACAActive,
+	TaskAborted,
+	/// Obsolete or reserved values
+	Other(u8),
+}
+
+impl Status {
+	/// Returns `Status` for a given status byte.
+	pub fn from(status: u8) -> Status {
+		use self::Status::*;
+		match status {
+			0x00 => Good,
+			0x02 => CheckCondition,
+			0x04 => ConditionMet,
+			0x08 => Busy,
+			0x18 => ReservationConflict,
+			0x28 => TaskSetFull,
+			0x30 => ACAActive,
+			0x40 => TaskAborted,
+			x => Other(x),
+		}
+	}
+}
+
+/// Errors reported by the host adapter (i.e. `DID_*` values from Linux's `scsi/scsi.h`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HostStatus {
+	/// `DID_OK`: no error
+	Ok,
+	/// `DID_NO_CONNECT`: couldn't connect before timeout period (e.g. the device is gone, or the link is down)
+	NoConnect,
+	/// `DID_BUS_BUSY`: bus stayed busy through time out period
+	BusBusy,
+	/// `DID_TIME_OUT`: timed out for other reason
+	TimeOut,
+	/// `DID_BAD_TARGET`: bad target
+	BadTarget,
+	/// `DID_ABORT`: told to abort for some other reason
+	Abort,
+	/// `DID_PARITY`: parity error
+	Parity,
+	/// `DID_ERROR`: internal error
+	Error,
+	/// `DID_RESET`: reset by somebody
+	Reset,
+	/// `DID_BAD_INTR`: got an interrupt we weren't expecting
+	BadInterrupt,
+	/// `DID_PASSTHROUGH`: force command past mid-layer
+	Passthrough,
+	/// `DID_SOFT_ERROR`: the low level driver wants a retry
+	SoftError,
+	/// `DID_IMM_RETRY`: retry without decrementing retry count
+	ImmediateRetry,
+	/// `DID_REQUEUE`: requeue command (no immediate retry) also without decrementing the retry count
+	Requeue,
+	/// `DID_TRANSPORT_DISRUPTED`: transport error disrupted execution and the driver blocked the port to recover the link
+	TransportDisrupted,
+	/// `DID_TRANSPORT_FAILFAST`: transport class fastfailed the I/O
+	TransportFailfast,
+	/// `DID_TARGET_FAILURE`: permanent target failure, do not retry on other paths
+	TargetFailure,
+	/// `DID_NEXUS_FAILURE`: permanent nexus failure, retry on other paths might yield different results
+	NexusFailure,
+	/// `DID_ALLOC_FAILURE`: space allocation on the device failed
+	AllocFailure,
+	/// `DID_MEDIUM_ERROR`: medium error
+	MediumError,
+	Other(u16),
+}
+
+impl HostStatus {
+	/// Returns `HostStatus` for a given `sg_io_hdr.host_status` value.
+	pub fn from(status: u16) -> HostStatus {
+		use self::HostStatus::*;
+		match status {
+			0x00 => Ok,
+			0x01 => NoConnect,
+			0x02 => BusBusy,
+			0x03 => TimeOut,
+			0x04 => BadTarget,
+			0x05 => Abort,
+			0x06 => Parity,
+			0x07 => Error,
+			0x08 => Reset,
+			0x09 => BadInterrupt,
+			0x0a => Passthrough,
+			0x0b => SoftError,
+			0x0c => ImmediateRetry,
+			0x0d => Requeue,
+			0x0e => TransportDisrupted,
+			0x0f => TransportFailfast,
+			0x10 => TargetFailure,
+			0x11 => NexusFailure,
+			0x12 => AllocFailure,
+			0x13 => MediumError,
+			x => Other(x),
+		}
+	}
+
+	/// Returns `sg_io_hdr.host_status` value for this `HostStatus`, i.e. the reverse of [`from`](#method.from).
+	pub fn code(&self) -> u16 {
+		use self::HostStatus::*;
+		match *self {
+			Ok => 0x00,
+			NoConnect => 0x01,
+			BusBusy => 0x02,
+			TimeOut => 0x03,
+			BadTarget => 0x04,
+			Abort => 0x05,
+			Parity => 0x06,
+			Error => 0x07,
+			Reset => 0x08,
+			BadInterrupt => 0x09,
+			Passthrough => 0x0a,
+			SoftError => 0x0b,
+			ImmediateRetry => 0x0c,
+			Requeue => 0x0d,
+			TransportDisrupted => 0x0e,
+			TransportFailfast => 0x0f,
+			TargetFailure => 0x10,
+			NexusFailure => 0x11,
+			AllocFailure => 0x12,
+			MediumError => 0x13,
+			Other(x) => x,
+		}
+	}
+}
+
+/// Errors reported by the low-level driver (i.e. `DRIVER_*` values from Linux's `scsi/scsi.h`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DriverStatus {
+	/// `DRIVER_OK`: no error
+	Ok,
+	/// `DRIVER_BUSY`
+	Busy,
+	/// `DRIVER_SOFT`
+	Soft,
+	/// `DRIVER_MEDIA`
+	Media,
+	/// `DRIVER_ERROR`
+	Error,
+	/// `DRIVER_INVALID`
+	Invalid,
+	/// `DRIVER_TIMEOUT`
+	Timeout,
+	/// `DRIVER_HARD`
+	Hard,
+	/// `DRIVER_SENSE`: not an error per se, only indicates that sense data is available
+	Sense,
+	Other(u8),
+}
+
+impl DriverStatus {
+	/// Returns `DriverStatus` for a given `sg_io_hdr.driver_status` value.
+	///
+	/// Upper 4 bits of the value (the so-called suggestion, `SUGGEST_*`) are ignored.
+	pub fn from(status: u16) -> DriverStatus {
+		use self::DriverStatus::*;
+		match status & 0x0f {
+			0x00 => Ok,
+			0x01 => Busy,
+			0x02 => Soft,
+			0x03 => Media,
+			0x04 => Error,
+			0x05 => Invalid,
+			0x06 => Timeout,
+			0x07 => Hard,
+			0x08 => Sense,
+			x => Other(x as u8),
+		}
+	}
+
+	/// Returns `sg_io_hdr.driver_status` value for this `DriverStatus`, without the suggestion bits; the reverse of [`from`](#method.from).
+	pub fn code(&self) -> u8 {
+		use self::DriverStatus::*;
+		match *self {
+			Ok => 0x00,
+			Busy => 0x01,
+			Soft => 0x02,
+			Media => 0x03,
+			Error => 0x04,
+			Invalid => 0x05,
+			Timeout => 0x06,
+			Hard => 0x07,
+			Sense => 0x08,
+			Other(x) => x,
+		}
+	}
+
+	/// Whether this status indicates driver failure
+	pub fn is_error(&self) -> bool {
+		*self != DriverStatus::Ok && *self != DriverStatus::Sense
+	}
+}
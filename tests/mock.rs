@@ -0,0 +1,331 @@
+extern crate hdd;
+
+use std::cell::RefCell;
+use std::time::Duration;
+
+use hdd::Direction;
+use hdd::ata::{ATADevice, ATATransport, Protocol, RegistersRead, RegistersWrite, RegistersReadExt, RegistersWriteExt};
+use hdd::ata::misc::{Misc, SelfTest};
+use hdd::ata::data::sct::{self, ERCTimer};
+use hdd::capture::{Capture, Recorder};
+use hdd::mock::{MockDevice, Failure};
+use hdd::scsi::{self, SCSICommon};
+use hdd::scsi::pages::SCSIPages;
+
+// SMART commands always go with this in LBA Mid/High
+fn smart_regs(features: u8, sector_count: u8, sector: u8) -> RegistersWrite {
+	RegistersWrite {
+		command: 0xb0,
+		features,
+		sector_count,
+		sector,
+		cyl_low: 0x4f,
+		cyl_high: 0xc2,
+		device: 0,
+	}
+}
+
+fn status(status: u8, sector_count: u8, sector: u8) -> RegistersRead {
+	RegistersRead {
+		error: 0,
+		sector_count,
+		sector,
+		cyl_low: 0x4f,
+		cyl_high: 0xc2,
+		device: 0,
+		status,
+	}
+}
+
+// makes the sum of all the bytes of 512-byte structure zero
+fn set_checksum(data: &mut [u8]) {
+	let sum = data[..511].iter().fold(0u8, |sum, &x| sum.wrapping_add(x));
+	data[511] = 0u8.wrapping_sub(sum);
+}
+
+// ATA strings are stored as big-endian words
+fn set_string(data: &mut [u8], word: usize, s: &str) {
+	for (i, pair) in s.as_bytes().chunks(2).enumerate() {
+		data[(word + i) * 2] = pair[1];
+		data[(word + i) * 2 + 1] = pair[0];
+	}
+}
+
+#[test]
+fn ata_identify_and_smart_data() {
+	let mut id = vec![0; 512];
+	// strings are padded with spaces
+	set_string(&mut id, 10, &format!("{:20}", "S3R14L"));
+	set_string(&mut id, 27, &format!("{:40}", "MOCK DRIVE"));
+	// word 82: SMART is supported; word 85: SMART is enabled
+	id[164] = 0x01;
+	id[170] = 0x01;
+	id[510] = 0xa5;
+	set_checksum(&mut id);
+
+	let mut data = vec![0; 512];
+	// temperature: id, flags (2 bytes), value, worst, raw (6 bytes), reserved
+	data[2 .. 2 + 12].copy_from_slice(&[194, 0x22, 0x00, 64, 50, 36, 0, 20, 0, 45, 0, 0]);
+	// reallocated sector count
+	data[14 .. 14 + 12].copy_from_slice(&[5, 0x33, 0x00, 100, 100, 3, 0, 0, 0, 0, 0, 0]);
+	data[367] = 0x5b; // off-line data collection capabilities
+	set_checksum(&mut data);
+
+	let mut thresh = vec![0; 512];
+	thresh[2 .. 4].copy_from_slice(&[194, 0]);
+	thresh[14 .. 16].copy_from_slice(&[5, 36]);
+	set_checksum(&mut thresh);
+
+	let mut dev = MockDevice::new();
+	dev.add_ata(Protocol::PIO, RegistersWrite { command: 0xec, features: 0, sector_count: 1, sector: 1, cyl_low: 0, cyl_high: 0, device: 0 }, &[], status(0x50, 0, 0), &id);
+	dev.add_ata(Protocol::PIO, smart_regs(0xd0, 1, 0), &[], status(0x50, 0, 0), &data);
+	dev.add_ata(Protocol::PIO, smart_regs(0xd1, 1, 0), &[], status(0x50, 0, 0), &thresh);
+	let dev = ATADevice::new(dev);
+
+	let id = dev.get_device_id().unwrap();
+	assert!(id.is_ata);
+	assert_eq!(id.model, "MOCK DRIVE");
+	assert_eq!(id.serial, "S3R14L");
+
+	let smart = dev.get_smart_data(&None).unwrap();
+	assert!(smart.checksum_valid);
+	assert!(smart.thresholds_checksum_valid);
+	assert!(smart.offline_capabilities.self_test);
+	assert_eq!(smart.attributes.len(), 2);
+
+	let temp = &smart.attributes[0];
+	assert_eq!((temp.id, temp.value, temp.worst, temp.thresh), (194, Some(64), Some(50), Some(0)));
+	let realloc = &smart.attributes[1];
+	assert_eq!((realloc.id, realloc.value, realloc.thresh), (5, Some(100), Some(36)));
+	assert!(realloc.pre_fail);
+}
+
+#[test]
+fn ata_unscripted_command_is_aborted() {
+	let mut dev = MockDevice::new();
+	dev.add_ata(Protocol::PIO, smart_regs(0xda, 0, 0), &[], status(0x50, 0, 0), &[]);
+	let dev = ATADevice::new(dev);
+
+	match dev.smart_read_log(0xe0, 1) {
+		Err(hdd::ata::misc::Error::Command(0x04)) => (),
+		ret => panic!("unexpected reply: {:?}", ret),
+	}
+}
+
+#[test]
+fn ata_read_log_ext_page_number() {
+	let mut dev = MockDevice::new();
+	dev.add_ata_ext(Protocol::PIO, RegistersWriteExt {
+		command: 0x2f,
+		features: 0,
+		sector_count: 1,
+		// page number (15:8) goes to LBA (39:32), page number (7:0) to LBA (15:8), log address to LBA (7:0)
+		lba: 0x01_0000_0204,
+		device: 0,
+	}, &[], RegistersReadExt { error: 0, sector_count: 0, lba: 0, device: 0, status: 0x50 }, &[0x42; 512]);
+	let dev = ATADevice::new(dev);
+
+	assert_eq!(dev.read_log_ext(0x04, 0x0102, 1).unwrap(), vec![0x42; 512]);
+}
+
+#[test]
+fn ata_sct_erc_is_matched_by_data_out() {
+	// the very same registers, told apart only by the key page sent to the device
+	let key_read = sct::key_page(sct::Action::ErrorRecoveryControl, 2, &[ERCTimer::Read as u16]);
+	let key_write = sct::key_page(sct::Action::ErrorRecoveryControl, 2, &[ERCTimer::Write as u16]);
+	let key_set = sct::key_page(sct::Action::ErrorRecoveryControl, 1, &[ERCTimer::Write as u16, 70]);
+
+	let mut dev = MockDevice::new();
+	dev.add_ata(Protocol::PIO, smart_regs(0xd6, 1, 0xe0), &key_read, status(0x50, 70, 0), &[]);
+	dev.add_ata(Protocol::PIO, smart_regs(0xd6, 1, 0xe0), &key_write, status(0x50, 0, 0), &[]);
+	// once set, write timer returns new value
+	dev.add_ata(Protocol::PIO, smart_regs(0xd6, 1, 0xe0), &key_set, status(0x50, 0, 0), &[]);
+	dev.add_ata(Protocol::PIO, smart_regs(0xd6, 1, 0xe0), &key_write, status(0x50, 70, 0), &[]);
+	let dev = ATADevice::new(dev);
+
+	assert_eq!(dev.get_sct_erc(ERCTimer::Read).unwrap(), 70);
+	assert_eq!(dev.get_sct_erc(ERCTimer::Write).unwrap(), 0);
+	dev.set_sct_erc(ERCTimer::Write, 70).unwrap();
+	assert_eq!(dev.get_sct_erc(ERCTimer::Write).unwrap(), 70);
+	// replies are exhausted, so the last one is repeated
+	assert_eq!(dev.get_sct_erc(ERCTimer::Write).unwrap(), 70);
+
+	// key page that was not scripted
+	assert!(dev.set_sct_erc(ERCTimer::Read, 70).is_err());
+}
+
+#[test]
+fn scsi_pages_temperature() {
+	// LOG SENSE, cumulative values, allocation length 4096
+	let log_sense = |page: u8| [0x4d, 0, 0b0100_0000 | page, 0, 0, 0, 0, 0x10, 0x00, 0];
+
+	let mut dev = MockDevice::new();
+	// supported pages: 00h, 0Dh
+	dev.add_scsi(&log_sense(0x00), &[], &[], &[0x00, 0, 0, 2, 0x00, 0x0d]);
+	// params 0000h (temperature) and 0001h (reference temperature)
+	dev.add_scsi(&log_sense(0x0d), &[], &[], &[
+		0x0d, 0, 0, 12,
+		0, 0, 0x03, 2, 0, 35,
+		0, 1, 0x03, 2, 0, 60,
+	]);
+
+	let mut pages = SCSIPages::new(&dev).unwrap();
+	assert_eq!(pages.supported_pages(), &[0x00, 0x0d]);
+	assert_eq!(pages.temperature().unwrap(), (Some(35), Some(60)));
+
+	// not in the list of supported pages, so it is not even sent
+	match pages.read_error_counters() {
+		Err(scsi::pages::Error::NotSupported) => (),
+		ret => panic!("unexpected reply: {:?}", ret),
+	}
+}
+
+#[test]
+fn scsi_unscripted_command_is_rejected() {
+	let dev = MockDevice::new();
+	let (sense, data) = dev.scsi_inquiry(false, 0).unwrap();
+	// fixed format sense: ILLEGAL REQUEST, INVALID COMMAND OPERATION CODE
+	assert_eq!((sense[2], sense[12], sense[13]), (0x05, 0x20, 0x00));
+	assert!(data.is_empty());
+
+	// ATA PASS-THROUGH is not supported either
+	match dev.do_ata_cmd(Protocol::PIO, Direction::None, &smart_regs(0xda, 0, 0), &[], None) {
+		Err(scsi::ATAError::NotSupported) => (),
+		ret => panic!("unexpected reply: {:?}", ret),
+	}
+}
+
+#[test]
+fn capture_replays_repeated_commands_and_failures() {
+	let mut status_before = vec![0; 512];
+	status_before[200] = 30;
+	let mut status_after = vec![0; 512];
+	status_after[200] = 31;
+
+	let mut dev = MockDevice::new();
+	dev.add_ata(Protocol::PIO, smart_regs(0xd5, 1, 0xe0), &[], status(0x50, 0, 0), &status_before);
+	dev.add_ata(Protocol::PIO, smart_regs(0xd5, 1, 0xe0), &[], status(0x50, 0, 0), &status_after);
+	dev.add_ata_failure(Protocol::PIO, smart_regs(0xd5, 1, 0xe1), &[], Failure::Host(0x03));
+
+	let recorder = Recorder::new(dev);
+	let log = recorder.log();
+	let dev = ATADevice::new(recorder);
+	assert_eq!(dev.get_sct_status().unwrap().temperature, Some(30));
+	assert_eq!(dev.get_sct_status().unwrap().temperature, Some(31));
+	assert!(dev.smart_read_log(0xe1, 1).is_err());
+
+	let capture = Capture {
+		device: None,
+		entries: log.borrow().clone(),
+	};
+	assert_eq!(capture.entries.len(), 3);
+
+	let dev = ATADevice::new(capture.replay());
+	assert_eq!(dev.get_sct_status().unwrap().temperature, Some(30));
+	assert_eq!(dev.get_sct_status().unwrap().temperature, Some(31));
+	match dev.smart_read_log(0xe1, 1) {
+		Err(hdd::ata::misc::Error::SCSI(scsi::ATAError::SCSI(scsi::Error::Host(status)))) => assert_eq!(status, scsi::status::HostStatus::TimeOut),
+		ret => panic!("unexpected reply: {:?}", ret),
+	}
+}
+
+#[test]
+fn ata_pass_through_fixed_sense_for_48_bit_command() {
+	// ATA PASS-THROUGH (16) with EXTEND bit set, non-data: READ NATIVE MAX ADDRESS EXT
+	let cdb = [0x85, (3 << 1) | 1, 0x20, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x40, 0x27, 0];
+	let regs = RegistersWriteExt { command: 0x27, features: 0, sector_count: 0, lba: 0, device: 0x40 };
+	// fixed format sense: RECOVERED ERROR, ATA PASS THROUGH INFORMATION AVAILABLE
+	let sense = |cmd_info: u8| [
+		0x70, 0, 0x01,
+		0x00, 0x50, 0x40, 0x00, // error, status, device, count (7:0)
+		10,
+		cmd_info, 0x2f, 0x6d, 0xa3, // flags, LBA (23:0)
+		0x00, 0x1d,
+		0, 0, 0, 0,
+	];
+
+	let mut dev = MockDevice::new();
+	// EXTEND only: upper bits are zero, so registers are complete
+	dev.add_scsi(&cdb, &[], &sense(0b1000_0000), &[]);
+	let (reply, _) = dev.ata_pass_through_16_ext(Protocol::PIO, Direction::None, &regs, &[], None).unwrap();
+	assert_eq!((reply.status, reply.device, reply.lba), (0x50, 0x40, 0xa3_6d2f));
+
+	// EXTEND, UPPER LBA NON-ZERO
+	dev.add_scsi(&cdb, &[], &sense(0b1010_0000), &[]);
+	match dev.ata_pass_through_16_ext(Protocol::PIO, Direction::None, &regs, &[], None) {
+		Err(scsi::ATAError::TruncatedRegisters) => (),
+		ret => panic!("unexpected reply: {:?}", ret),
+	}
+}
+
+#[test]
+fn ata_pass_through_rejects_invalid_transfers() {
+	// nothing is scripted, so anything that reaches the device is rejected with sense rather than InvalidTransfer
+	let dev = MockDevice::new();
+	let regs = |sector_count| RegistersWrite { command: 0xb0, features: 0xd6, sector_count, sector: 0xe0, cyl_low: 0x4f, cyl_high: 0xc2, device: 0 };
+
+	for &(dir, sector_count, data_out) in &[
+		(Direction::Both, 1, &[0; 512][..]),
+		// 0 means either 256 blocks or no data at all, depending on the command
+		(Direction::From, 0, &[][..]),
+		(Direction::To, 0, &[][..]),
+		// data out does not match sector count
+		(Direction::To, 1, &[0; 100][..]),
+		(Direction::To, 2, &[0; 512][..]),
+	] {
+		match dev.ata_pass_through_16(Protocol::PIO, dir, &regs(sector_count), data_out, None) {
+			Err(scsi::ATAError::InvalidTransfer) => (),
+			ret => panic!("unexpected reply to {:?} with sector count {}: {:?}", dir, sector_count, ret),
+		}
+		match dev.ata_pass_through_12(Protocol::PIO, dir, &regs(sector_count), data_out, None) {
+			Err(scsi::ATAError::InvalidTransfer) => (),
+			ret => panic!("unexpected reply to {:?} with sector count {}: {:?}", dir, sector_count, ret),
+		}
+	}
+
+	// same for devices that speak ATA natively
+	let mut dev = MockDevice::new();
+	dev.add_ata(Protocol::PIO, regs(1), &[0; 512], status(0x50, 0, 0), &[]);
+	let dev = ATADevice::new(dev);
+	assert!(dev.ata_transfer(Protocol::PIO, Direction::To, &regs(1), &[0; 512], None).is_ok());
+	match dev.ata_transfer(Protocol::PIO, Direction::Both, &regs(1), &[0; 512], None) {
+		Err(scsi::ATAError::InvalidTransfer) => (),
+		ret => panic!("unexpected reply: {:?}", ret),
+	}
+}
+
+// keeps timeouts of the commands it was asked to execute, replying with good status to everything
+#[derive(Default)]
+struct Timeouts {
+	sent: RefCell<Vec<(u8, Option<Duration>)>>,
+}
+
+impl ATATransport for Timeouts {
+	fn do_ata_cmd(&self, _: Protocol, _: Direction, regs: &RegistersWrite, _: &[u8], timeout: Option<Duration>) -> Result<(RegistersRead, Vec<u8>), scsi::ATAError> {
+		self.sent.borrow_mut().push((regs.sector, timeout));
+		Ok((status(0x50, 0, 0), vec![]))
+	}
+	fn do_ata_cmd_ext(&self, _: Protocol, _: Direction, _: &RegistersWriteExt, _: &[u8], _: Option<Duration>) -> Result<(RegistersReadExt, Vec<u8>), scsi::ATAError> {
+		unimplemented!()
+	}
+}
+
+#[test]
+fn ata_captive_self_test_timeout() {
+	let dev = ATADevice::new(Timeouts::default());
+	dev.start_self_test(SelfTest::Short, false, None).unwrap();
+	dev.start_self_test(SelfTest::Extended, true, Some(Duration::from_secs(3600))).unwrap();
+	dev.start_selective_self_test(&[(0, 1000)], true, Some(Duration::from_secs(60))).unwrap();
+	dev.abort_self_test().unwrap();
+
+	let dev = dev.unwrap();
+	assert_eq!(*dev.sent.borrow(), vec![
+		// SMART EXECUTE OFF-LINE IMMEDIATE subcommand in LBA Low, with captive mode bit
+		(0x01, None),
+		(0x82, Some(Duration::from_secs(3600))),
+		// SMART WRITE LOG (selective self-test log), then the test itself
+		(0x09, None),
+		(0x84, Some(Duration::from_secs(60))),
+		(0x7f, None),
+	]);
+}
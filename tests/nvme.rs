@@ -0,0 +1,167 @@
+extern crate hdd;
+
+use std::cell::RefCell;
+
+use hdd::Direction;
+use hdd::nvme::{AdminCmd, Error, NVMeCommon};
+use hdd::nvme::data::{id, smart};
+
+// replies to GET LOG PAGE with zero-filled data, keeping every command it was asked to execute
+#[derive(Default)]
+struct Device {
+	sent: RefCell<Vec<AdminCmd>>,
+}
+
+impl NVMeCommon for Device {
+	fn do_admin_cmd(&self, dir: Direction, cmd: &AdminCmd, data_len: usize, _data_out: &[u8]) -> Result<(u32, Vec<u8>), Error> {
+		self.sent.borrow_mut().push(cmd.clone());
+		match dir {
+			Direction::From => Ok((0, vec![0; data_len])),
+			_ => Ok((0, vec![])),
+		}
+	}
+}
+
+#[test]
+fn error_log_numd() {
+	let dev = Device::default();
+	// zero-filled entries are empty, and thus skipped
+	assert!(dev.error_log(2).unwrap().is_empty());
+
+	let sent = dev.sent.borrow();
+	assert_eq!(sent.len(), 1);
+	// two 64-byte entries are 32 dwords; NUMD is 0's based, log identifier 01h
+	assert_eq!(sent[0].cdw10, 31 << 16 | 0x01);
+	assert_eq!(sent[0].cdw11, 0);
+}
+
+#[test]
+fn error_log_of_no_entries_is_rejected() {
+	let dev = Device::default();
+	match dev.error_log(0) {
+		Err(Error::InvalidArgument(_)) => (),
+		ret => panic!("unexpected reply: {:?}", ret),
+	}
+	match dev.get_log_page(0x01, 0xffff_ffff, 0) {
+		Err(Error::InvalidArgument(_)) => (),
+		ret => panic!("unexpected reply: {:?}", ret),
+	}
+	// nothing should reach the device
+	assert!(dev.sent.borrow().is_empty());
+}
+
+fn put(data: &mut [u8], offset: usize, bytes: &[u8]) {
+	data[offset .. offset + bytes.len()].copy_from_slice(bytes);
+}
+
+#[test]
+fn identify_controller() {
+	let mut data = vec![0; id::ID_LEN];
+	put(&mut data, 0, &0x144du16.to_le_bytes()); // VID
+	put(&mut data, 2, &0x144du16.to_le_bytes()); // SSVID
+	put(&mut data, 4, format!("{:20}", "S4EWNX0N123456").as_bytes()); // SN
+	put(&mut data, 24, format!("{:40}", "Samsung SSD 970 EVO Plus 1TB").as_bytes()); // MN
+	put(&mut data, 64, b"2B2QEXM7"); // FR
+	data[77] = 9; // MDTS
+	put(&mut data, 78, &4u16.to_le_bytes()); // CNTLID
+	put(&mut data, 80, &0x0001_0300u32.to_le_bytes()); // VER: 1.3.0
+	put(&mut data, 256, &0b1_0111u16.to_le_bytes()); // OACS: security, format, firmware, self-test
+	data[260] = 0b0110; // FRMW: 3 slots, slot 1 is writable
+	data[262] = 63; // ELPE, 0's based
+	data[263] = 4; // NPSS, 0's based
+	put(&mut data, 266, &358u16.to_le_bytes()); // WCTEMP
+	put(&mut data, 268, &358u16.to_le_bytes()); // CCTEMP
+	put(&mut data, 280, &1_000_204_886_016u64.to_le_bytes()); // TNVMCAP
+	put(&mut data, 316, &85u16.to_le_bytes()); // EDSTT
+	put(&mut data, 516, &1u32.to_le_bytes()); // NN
+	put(&mut data, 520, &0b0101_1111u16.to_le_bytes()); // ONCS
+	data[525] = 1; // VWC
+	put(&mut data, 768, b"nqn.2014.08.org.nvmexpress:144d144dS4EWNX0N123456"); // SUBNQN, NUL-terminated
+
+	let id = id::parse_controller(&data).unwrap();
+	assert_eq!((id.vendor_id, id.subsystem_vendor_id), (0x144d, 0x144d));
+	assert_eq!(id.serial, "S4EWNX0N123456");
+	assert_eq!(id.model, "Samsung SSD 970 EVO Plus 1TB");
+	assert_eq!(id.firmware, "2B2QEXM7");
+	assert_eq!(id.max_transfer_size, Some(9));
+	assert_eq!(id.controller_id, 4);
+	assert_eq!(id.version, Some((1, 3, 0)));
+	assert!(id.admin_commands.security && id.admin_commands.format_nvm && id.admin_commands.firmware && id.admin_commands.self_test);
+	assert!(!id.admin_commands.namespace_management);
+	assert_eq!((id.firmware_slots, id.firmware_slot1_readonly), (3, false));
+	assert_eq!((id.error_log_entries, id.power_states), (64, 5));
+	assert_eq!((id.warning_temp, id.critical_temp), (Some(358), Some(358)));
+	assert_eq!((id.total_capacity, id.unallocated_capacity), (1_000_204_886_016, 0));
+	assert_eq!(id.self_test_time, Some(85));
+	assert_eq!(id.namespaces, 1);
+	assert!(id.nvm_commands.timestamp && !id.nvm_commands.reservations && !id.nvm_commands.verify);
+	assert!(id.volatile_write_cache);
+	assert_eq!(id.subsystem_nqn, "nqn.2014.08.org.nvmexpress:144d144dS4EWNX0N123456");
+
+	assert!(id::parse_controller(&data[.. id::ID_LEN - 1]).is_none());
+}
+
+#[test]
+fn identify_namespace() {
+	let mut data = vec![0; id::ID_LEN];
+	put(&mut data, 0, &1_953_525_168u64.to_le_bytes()); // NSZE
+	put(&mut data, 8, &1_953_525_168u64.to_le_bytes()); // NCAP
+	put(&mut data, 16, &123_456_789u64.to_le_bytes()); // NUSE
+	data[25] = 1; // NLBAF, 0's based
+	data[26] = 1; // FLBAS: format #1
+	put(&mut data, 48, &1_000_204_886_016u64.to_le_bytes()); // NVMCAP
+	put(&mut data, 120, &[0x00, 0x25, 0x38, 0x5b, 0x91, 0xb0, 0x1a, 0x2c]); // EUI64
+	put(&mut data, 128, &(9u32 << 16 | 2 << 24).to_le_bytes()); // LBAF0: 512 bytes, degraded performance
+	put(&mut data, 132, &(12u32 << 16 | 8).to_le_bytes()); // LBAF1: 4096 bytes, 8 bytes of metadata, best performance
+
+	let ns = id::parse_namespace(&data).unwrap();
+	assert_eq!((ns.size, ns.capacity, ns.utilization), (1_953_525_168, 1_953_525_168, 123_456_789));
+	assert!(!ns.thin_provisioning);
+	assert_eq!(ns.lba_formats.len(), 2);
+	assert_eq!((ns.lba_formats[0].data_size, ns.lba_formats[0].metadata_size, ns.lba_formats[0].relative_performance), (512, 0, 2));
+	assert_eq!((ns.lba_formats[1].data_size, ns.lba_formats[1].metadata_size, ns.lba_formats[1].relative_performance), (4096, 8, 0));
+	assert_eq!(ns.lba_format, 1);
+	assert_eq!(ns.block_size(), Some(4096));
+	assert_eq!(ns.nvm_capacity, 1_000_204_886_016);
+	assert_eq!(ns.nguid, None);
+	assert_eq!(ns.eui64.as_deref(), Some("0025385b91b01a2c"));
+
+	assert!(id::parse_namespace(&data[.. id::ID_LEN - 1]).is_none());
+}
+
+#[test]
+fn smart_log() {
+	let mut data = vec![0; smart::SMART_LOG_LEN];
+	data[0] = 0b0000_1001; // critical warning: available spare, read only
+	put(&mut data, 1, &310u16.to_le_bytes()); // composite temperature
+	data[3] = 10; // available spare
+	data[4] = 10; // available spare threshold
+	data[5] = 103; // percentage used, can exceed 100
+	put(&mut data, 32, &12_345_678u64.to_le_bytes()); // data units read
+	// data units written do not fit into 64 bits
+	put(&mut data, 48, &1u64.to_le_bytes());
+	put(&mut data, 56, &1u64.to_le_bytes());
+	put(&mut data, 112, &1_234u64.to_le_bytes()); // power cycles
+	put(&mut data, 128, &20_000u64.to_le_bytes()); // power on hours
+	put(&mut data, 160, &3u64.to_le_bytes()); // media errors
+	put(&mut data, 192, &5u32.to_le_bytes()); // warning composite temperature time
+	put(&mut data, 196, &1u32.to_le_bytes()); // critical composite temperature time
+	// sensors 1 and 3 are implemented
+	put(&mut data, 200, &305u16.to_le_bytes());
+	put(&mut data, 204, &320u16.to_le_bytes());
+
+	let log = smart::parse_smart_log(&data).unwrap();
+	assert!(log.critical_warning.any());
+	assert!(log.critical_warning.available_spare && log.critical_warning.read_only);
+	assert!(!log.critical_warning.temperature && !log.critical_warning.reliability);
+	assert_eq!(log.temperature, 310);
+	assert_eq!(log.temperature_sensors, vec![Some(305), None, Some(320), None, None, None, None, None]);
+	assert_eq!((log.available_spare, log.available_spare_threshold, log.percentage_used), (10, 10, 103));
+	assert_eq!(log.data_units_read, 12_345_678);
+	assert_eq!(log.data_units_written, u64::MAX);
+	assert_eq!((log.power_cycles, log.power_on_hours, log.unsafe_shutdowns), (1_234, 20_000, 0));
+	assert_eq!((log.media_errors, log.error_log_entries), (3, 0));
+	assert_eq!((log.warning_temp_time, log.critical_temp_time), (5, 1));
+
+	assert!(smart::parse_smart_log(&data[.. smart::SMART_LOG_LEN - 1]).is_none());
+}
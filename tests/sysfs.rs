@@ -0,0 +1,256 @@
+#![cfg(target_os = "linux")]
+
+extern crate hdd;
+
+use std::env;
+use std::fs;
+use std::os::unix::fs::symlink;
+use std::path::{Path, PathBuf};
+use std::process;
+
+use hdd::device::{DeviceInfo, Enumerator, Type};
+
+/// Fake sysfs tree, removed once dropped.
+struct Sysfs {
+	base: PathBuf,
+	root: PathBuf,
+}
+
+impl Sysfs {
+	fn new(test: &str) -> Self {
+		let base = env::temp_dir().join(format!("hdd-sysfs-{}-{}", test, process::id()));
+		// path to the root is deliberately made to look like something device paths are matched against
+		let root = base.join("usb1/virtio0/ata1");
+		let _ = fs::remove_dir_all(&base);
+		fs::create_dir_all(&root).unwrap();
+		Sysfs { base, root }
+	}
+
+	fn dir(&self, path: &str) -> PathBuf {
+		let path = self.root.join(path);
+		fs::create_dir_all(&path).unwrap();
+		path
+	}
+
+	fn file(&self, path: &str, contents: &str) {
+		let path = self.root.join(path);
+		fs::create_dir_all(path.parent().unwrap()).unwrap();
+		fs::write(path, contents).unwrap();
+	}
+
+	/// Makes `path` a symlink to `target`, both relative to the root.
+	fn link(&self, path: &str, target: &str) {
+		let path = self.root.join(path);
+		fs::create_dir_all(path.parent().unwrap()).unwrap();
+		symlink(self.dir(target), path).unwrap();
+	}
+
+	/// Adds SCSI device `dev` (path under `devices/`, named after its H:C:T:L address), optionally with a block device, and with a generic device.
+	fn scsi_device(&self, dev: &str, vendor: &str, model: &str, block: Option<&str>, generic: &str) {
+		let dev = format!("devices/{}", dev);
+		self.file(&format!("{}/vendor", dev), &format!("{:8}\n", vendor));
+		self.file(&format!("{}/model", dev), &format!("{:16}\n", model));
+		self.link(&format!("{}/driver", dev), "bus/scsi/drivers/sd");
+		self.link(&format!("{}/subsystem", dev), "bus/scsi");
+
+		if let Some(block) = block {
+			self.block_device(&format!("{}/block/{}", dev, block), block, &dev);
+		}
+
+		let generic_path = format!("{}/scsi_generic/{}", dev, generic);
+		self.link(&format!("{}/device", generic_path), &dev);
+		self.link(&format!("class/scsi_generic/{}", generic), &generic_path);
+		if block.is_some() {
+			self.link(&format!("{}/generic", dev), &generic_path);
+		}
+	}
+
+	/// Adds block device at `path` (which is relative to the root), with `device` being its parent device (if any).
+	fn block_device(&self, path: &str, name: &str, device: &str) {
+		self.file(&format!("{}/uevent", path), &format!("MAJOR=8\nMINOR=0\nDEVNAME={}\nDEVTYPE=disk\n", name));
+		self.file(&format!("{}/size", path), "7814037168\n");
+		self.file(&format!("{}/removable", path), "0\n");
+		self.file(&format!("{}/queue/rotational", path), "1\n");
+		if ! device.is_empty() {
+			self.link(&format!("{}/device", path), device);
+		}
+		self.link(&format!("class/block/{}", name), path);
+	}
+
+	fn scsi_host(&self, host: &str, proc_name: &str) {
+		self.file(&format!("class/scsi_host/{}/proc_name", host), &format!("{}\n", proc_name));
+	}
+
+	fn enumerator(&self) -> Enumerator {
+		Enumerator::with_root(&self.root)
+	}
+}
+
+impl Drop for Sysfs {
+	fn drop(&mut self) {
+		let _ = fs::remove_dir_all(&self.base);
+	}
+}
+
+fn find<'a>(devices: &'a [DeviceInfo], path: &str) -> &'a DeviceInfo {
+	devices.iter()
+		.find(|d| d.path == Path::new(path))
+		.unwrap_or_else(|| panic!("{} not found in {:?}", path, devices))
+}
+
+fn paths(devices: &[DeviceInfo]) -> Vec<String> {
+	let mut paths: Vec<_> = devices.iter()
+		.map(|d| d.path.to_string_lossy().into_owned())
+		.collect();
+	paths.sort();
+	paths
+}
+
+#[test]
+fn aacraid() {
+	let sys = Sysfs::new("aacraid");
+	let host = "pci0000:00/0000:00:01.0/0000:01:00.0/host0";
+	// logical volume
+	sys.scsi_device(&format!("{}/target0:0:0/0:0:0:0", host), "Adaptec", "RAID1", Some("sda"), "sg0");
+	// physical disk behind it, only accessible through its generic device
+	sys.scsi_device(&format!("{}/target0:1:0/0:1:0:0", host), "ATA", "ST4000DM004", None, "sg1");
+	sys.scsi_host("host0", "aacraid");
+
+	let devices = sys.enumerator().list_info().unwrap();
+	assert_eq!(paths(&devices), vec!["/dev/sda", "/dev/sg1"]);
+
+	let sda = find(&devices, "/dev/sda");
+	assert_eq!(sda.block.as_deref(), Some("sda"));
+	assert_eq!(sda.generic.as_deref(), Some("sg0"));
+	assert_eq!(sda.vendor.as_deref(), Some("Adaptec"));
+	assert_eq!(sda.driver.as_deref(), Some("aacraid"));
+	assert_eq!(sda.transport, Some(Type::SCSI));
+	assert_eq!(sda.size, Some(7814037168 * 512));
+	assert_eq!(sda.address.map(|a| (a.host, a.channel, a.target, a.lun)), Some((0, 0, 0, 0)));
+
+	let sg1 = find(&devices, "/dev/sg1");
+	assert_eq!(sg1.block, None);
+	assert_eq!(sg1.generic.as_deref(), Some("sg1"));
+	assert_eq!(sg1.driver.as_deref(), Some("aacraid"));
+	assert_eq!(sg1.transport, Some(Type::SAT));
+	assert_eq!(sg1.address.map(|a| (a.host, a.channel, a.target, a.lun)), Some((0, 1, 0, 0)));
+
+	// SATL is in the controller firmware, there's no libata port to be found
+	assert_eq!(sys.enumerator().get_type_reason("sg1"), (Type::SAT, "vendor is \"ATA\"".to_string()));
+}
+
+#[test]
+fn virtio_scsi() {
+	let sys = Sysfs::new("virtio-scsi");
+	sys.link("devices/pci0000:00/0000:00:04.0/virtio1/driver", "bus/virtio/drivers/virtio_scsi");
+	sys.scsi_device("pci0000:00/0000:00:04.0/virtio1/host2/target2:0:0/2:0:0:0", "QEMU", "QEMU HARDDISK", Some("sda"), "sg0");
+	sys.scsi_host("host2", "virtio_scsi");
+
+	let devices = sys.enumerator().list_info().unwrap();
+	assert_eq!(paths(&devices), vec!["/dev/sda"]);
+
+	let sda = find(&devices, "/dev/sda");
+	assert_eq!(sda.transport, Some(Type::VirtioSCSI));
+	assert_eq!(sda.driver.as_deref(), Some("virtio_scsi"));
+
+	assert_eq!(sys.enumerator().get_type_reason("sda"), (Type::VirtioSCSI, "device is attached to SCSI host host2 of virtio1".to_string()));
+	// same device, reached through its generic device
+	assert_eq!(sys.enumerator().get_type_reason("sg0").0, Type::VirtioSCSI);
+}
+
+#[test]
+fn virtio_blk() {
+	let sys = Sysfs::new("virtio-blk");
+	sys.link("devices/pci0000:00/0000:00:05.0/virtio2/driver", "bus/virtio/drivers/virtio_blk");
+	sys.block_device("devices/pci0000:00/0000:00:05.0/virtio2/block/vda", "vda", "devices/pci0000:00/0000:00:05.0/virtio2");
+
+	assert_eq!(paths(&sys.enumerator().list_info().unwrap()), Vec::<String>::new());
+	assert_eq!(sys.enumerator().get_type_reason("vda"), (Type::VirtioBlk, "driver of virtio2 is virtio_blk".to_string()));
+}
+
+#[test]
+fn md() {
+	let sys = Sysfs::new("md");
+	sys.scsi_device("pci0000:00/0000:00:1f.2/ata1/host0/target0:0:0/0:0:0:0", "ATA", "ST4000DM004", Some("sda"), "sg0");
+	sys.scsi_device("pci0000:00/0000:00:1f.2/ata2/host1/target1:0:0/1:0:0:0", "ATA", "ST4000DM004", Some("sdb"), "sg1");
+	sys.scsi_host("host0", "ahci");
+	sys.scsi_host("host1", "ahci");
+	// RAID1 of the two above, plus a loop device
+	sys.block_device("devices/virtual/block/md0", "md0", "");
+	sys.block_device("devices/virtual/block/loop0", "loop0", "");
+
+	let devices = sys.enumerator().list_info().unwrap();
+	assert_eq!(paths(&devices), vec!["/dev/sda", "/dev/sdb"]);
+
+	let sda = find(&devices, "/dev/sda");
+	assert_eq!(sda.transport, Some(Type::SAT));
+	assert_eq!(sda.driver.as_deref(), Some("ahci"));
+	assert_eq!(sys.enumerator().get_type_reason("sdb"), (Type::SAT, "vendor is \"ATA\", device is attached to libata port ata2".to_string()));
+
+	// md devices have no `device` directory
+	assert_eq!(sys.enumerator().get_type_reason("md0").0, Type::SCSI);
+}
+
+#[test]
+fn missing_classes() {
+	let sys = Sysfs::new("empty");
+	assert!(sys.enumerator().list_info().is_err());
+
+	sys.dir("class/scsi_generic");
+	assert_eq!(sys.enumerator().list_info().unwrap().len(), 0);
+}
+
+#[test]
+fn nvme() {
+	let sys = Sysfs::new("nvme");
+	sys.link("devices/pci0000:00/0000:00:1d.0/0000:3d:00.0/driver", "bus/pci/drivers/nvme");
+	sys.link("devices/pci0000:00/0000:00:1d.1/0000:3e:00.0/driver", "bus/pci/drivers/nvme");
+	for &(ctrl, pci, model) in &[
+		("nvme0", "pci0000:00/0000:00:1d.0/0000:3d:00.0", "MULTIPATH SSD"),
+		("nvme1", "pci0000:00/0000:00:1d.1/0000:3e:00.0", "PLAIN SSD"),
+	] {
+		let path = format!("devices/{}/nvme/{}", pci, ctrl);
+		sys.file(&format!("{}/model", path), &format!("{:40}\n", model));
+		sys.file(&format!("{}/serial", path), &format!("{:20}\n", format!("S3R14L-{}", ctrl)));
+		sys.link(&format!("{}/subsystem", path), "class/nvme");
+		sys.link(&format!("{}/device", path), &format!("devices/{}", pci));
+		sys.link(&format!("class/nvme/{}", ctrl), &path);
+	}
+
+	// native multipath: namespace belongs to the subsystem, and the path through the controller is hidden
+	let subsys = "devices/virtual/nvme-subsystem/nvme-subsys0";
+	sys.file(&format!("{}/model", subsys), &format!("{:40}\n", "MULTIPATH SSD"));
+	sys.link(&format!("{}/subsystem", subsys), "class/nvme-subsystem");
+	sys.block_device(&format!("{}/nvme0n1", subsys), "nvme0n1", subsys);
+	let hidden = "devices/pci0000:00/0000:00:1d.0/0000:3d:00.0/nvme/nvme0/nvme0c0n1";
+	sys.block_device(hidden, "nvme0c0n1", "devices/pci0000:00/0000:00:1d.0/0000:3d:00.0/nvme/nvme0");
+	sys.file(&format!("{}/hidden", hidden), "1\n");
+
+	// no multipath: namespace is a child of the controller
+	sys.block_device("devices/pci0000:00/0000:00:1d.1/0000:3e:00.0/nvme/nvme1/nvme1n1", "nvme1n1", "devices/pci0000:00/0000:00:1d.1/0000:3e:00.0/nvme/nvme1");
+
+	let devices = sys.enumerator().list_info().unwrap();
+	assert_eq!(paths(&devices), vec!["/dev/nvme0", "/dev/nvme0n1", "/dev/nvme1", "/dev/nvme1n1"]);
+
+	for dev in &devices {
+		assert_eq!(dev.transport, Some(Type::NVMe), "{:?}", dev.path);
+	}
+
+	let nvme0 = find(&devices, "/dev/nvme0");
+	assert_eq!(nvme0.model.as_deref(), Some("MULTIPATH SSD"));
+	assert_eq!(nvme0.serial.as_deref(), Some("S3R14L-nvme0"));
+	assert_eq!(nvme0.driver.as_deref(), Some("nvme"));
+	assert_eq!((nvme0.block.as_ref(), nvme0.size), (None, None));
+
+	let nvme0n1 = find(&devices, "/dev/nvme0n1");
+	assert_eq!(nvme0n1.model.as_deref(), Some("MULTIPATH SSD"));
+	assert_eq!(nvme0n1.block.as_deref(), Some("nvme0n1"));
+
+	let nvme1n1 = find(&devices, "/dev/nvme1n1");
+	assert_eq!(nvme1n1.model.as_deref(), Some("PLAIN SSD"));
+	assert_eq!(nvme1n1.driver.as_deref(), Some("nvme"));
+
+	assert_eq!(sys.enumerator().get_type_reason("nvme0n1"), (Type::NVMe, "device subsystem is nvme-subsystem".to_string()));
+	assert_eq!(sys.enumerator().get_type_reason("nvme1n1"), (Type::NVMe, "device subsystem is nvme".to_string()));
+	assert_eq!(sys.enumerator().get_type_reason("nvme1").0, Type::NVMe);
+}